@@ -14,21 +14,36 @@ impl<T: Clone> Sender<T> {
 
     pub fn subscribe(&self) -> Receiver<T> {
         let (tx, rx) = swap_channel::new();
-        self.0.borrow_mut().push(tx);
+        let mut senders = self.0.borrow_mut();
+        senders.retain(swap_channel::Sender::is_connected);
+        senders.push(tx);
         rx
     }
 
     pub fn send(&self, x: T) {
-        let senders = self.0.borrow();
-        for (i, tx) in senders.iter().enumerate() {
-            if i == senders.len() - 1 {
-                tx.send(x);
-                break;
-            } else {
+        let mut senders = self.0.borrow_mut();
+        senders.retain(swap_channel::Sender::is_connected);
+        if let Some((last, rest)) = senders.split_last() {
+            for tx in rest {
                 tx.send(x.clone());
             }
+            last.send(x);
         }
     }
+
+    pub fn receiver_count(&self) -> usize {
+        let mut senders = self.0.borrow_mut();
+        senders.retain(swap_channel::Sender::is_connected);
+        senders.len()
+    }
+
+    // Undrained values per live receiver, in subscription order; a receiver whose backlog keeps
+    // growing is never being read.
+    pub fn backlogs(&self) -> Vec<usize> {
+        let mut senders = self.0.borrow_mut();
+        senders.retain(swap_channel::Sender::is_connected);
+        Vec::from_iter(senders.iter().map(swap_channel::Sender::backlog))
+    }
 }
 
 pub fn new<T: Clone>() -> (Sender<T>, Receiver<T>) {
@@ -4,12 +4,12 @@ use std::io::{BufRead, BufReader, Write};
 use std::{collections::HashMap, fs::File, path::PathBuf};
 
 use clap::Parser;
-use sat::{Atom, Literal, RuleIndex, format_rule, parse, sanitize_rule};
+use sat::{Atom, Literal, RuleIndex, format_rule, input, parse, sanitize_rule};
 
 /// Translates an EDRAT proof int a DRAT proof.
 #[derive(Parser)]
 struct Opts {
-    /// Sets the input CNF file to use
+    /// Sets the input CNF file to use, optionally compressed, or `-` for stdin
     #[clap()]
     cnf: PathBuf,
 
@@ -24,7 +24,7 @@ struct Opts {
 
 fn main() {
     let opts = Opts::parse();
-    let base_rules = Vec::from_iter(parse::rules(BufReader::new(File::open(opts.cnf).unwrap())));
+    let base_rules = Vec::from_iter(parse::rules(input::open(opts.cnf).unwrap()));
     let edrat_stream = parse::Scanner::new(BufReader::new(File::open(opts.edrat).unwrap()).lines());
     let output = File::create(opts.drat).unwrap();
     Runner::new(base_rules, edrat_stream, output).run();
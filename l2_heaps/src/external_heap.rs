@@ -1,23 +1,37 @@
-use std::{collections::HashMap, hash::Hash, mem, ptr};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+    mem, ptr,
+};
 
 use index_list::{Index, IndexList};
 
-use crate::ExternalEntry;
+use crate::{ExternalEntry, HeapOrder};
 
-pub(crate) struct ExternalHeap<'a, K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V> {
+pub(crate) struct ExternalHeap<'a, K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, O, S> {
     k1: &'a K1,
+    order: &'a O,
     external: Option<ExternalEntry>,
-    map: &'a mut HashMap<(K1, K2), Index>,
+    map: &'a mut HashMap<(K1, K2), Index, S>,
     values: &'a mut IndexList<HeapEntry<K2, V>>,
 }
-impl<'a, K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V> ExternalHeap<'a, K1, K2, V> {
+impl<'a, K1, K2, V, O, S> ExternalHeap<'a, K1, K2, V, O, S>
+where
+    K1: Clone + Eq + Hash,
+    K2: Clone + Eq + Hash,
+    O: HeapOrder<K2>,
+    S: BuildHasher,
+{
     pub(crate) fn new(
         k1: &'a K1,
-        map: &'a mut HashMap<(K1, K2), Index>,
+        order: &'a O,
+        map: &'a mut HashMap<(K1, K2), Index, S>,
         values: &'a mut IndexList<HeapEntry<K2, V>>,
     ) -> Self {
         Self {
             k1,
+            order,
             external: None,
             map,
             values,
@@ -26,12 +40,14 @@ impl<'a, K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V> ExternalHeap<'a, K1,
 
     pub(crate) fn from_external(
         k1: &'a K1,
+        order: &'a O,
         external: ExternalEntry,
-        map: &'a mut HashMap<(K1, K2), Index>,
+        map: &'a mut HashMap<(K1, K2), Index, S>,
         values: &'a mut IndexList<HeapEntry<K2, V>>,
     ) -> Self {
         Self {
             k1,
+            order,
             external: Some(external),
             map,
             values,
@@ -130,7 +146,7 @@ impl<'a, K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V> ExternalHeap<'a, K1,
             return None;
         }
         let parent = self.values.get(parent_ind).unwrap();
-        if entry.k2 <= parent.k2 {
+        if self.order.cmp(&entry.k2, &parent.k2) != Ordering::Greater {
             return None;
         }
         self.map.insert((self.k1.clone(), parent.k2.clone()), ind);
@@ -150,13 +166,13 @@ impl<'a, K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V> ExternalHeap<'a, K1,
         } else {
             let left_child = self.values.get(left_child_ind).unwrap();
             let right_child = self.values.get(right_child_ind).unwrap();
-            if left_child.k2 >= right_child.k2 {
+            if self.order.cmp(&left_child.k2, &right_child.k2) != Ordering::Less {
                 (left_child_ind, left_child)
             } else {
                 (right_child_ind, right_child)
             }
         };
-        if entry.k2 >= chosen_child.k2 {
+        if self.order.cmp(&entry.k2, &chosen_child.k2) != Ordering::Less {
             return None;
         }
         self.map
@@ -199,4 +215,18 @@ impl<K2, V> HeapEntry<K2, V> {
     pub(crate) fn as_refs(&self) -> (&K2, &V) {
         (&self.k2, &self.value)
     }
+
+    pub(crate) fn as_mut_refs(&mut self) -> (&K2, &mut V) {
+        (&self.k2, &mut self.value)
+    }
+
+    pub(crate) fn into_parts(self) -> (K2, V) {
+        (self.k2, self.value)
+    }
+
+    pub(crate) fn children(&self) -> impl Iterator<Item = Index> {
+        [self.left_child, self.right_child]
+            .into_iter()
+            .filter(|ind| !ind.is_none())
+    }
 }
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, hash::Hash, iter, mem};
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash, RandomState},
+    iter, mem,
+};
 
 use arrayvec::ArrayVec;
 use derive_where::derive_where;
@@ -6,13 +10,27 @@ use index_list::{Index, IndexList};
 
 use self::external_heap::{ExternalHeap, HeapEntry};
 
+pub use self::order::{ByCmp, ByKey, HeapOrder, Max, Min};
+
 mod external_heap;
+mod order;
+#[cfg(feature = "serde")]
+mod serde_impls;
 
-#[derive_where(Default)]
-pub struct L2Heaps<K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V, const LIM: usize = 2> {
-    map: HashMap<(K1, K2), Index>,
-    ranges: HashMap<K1, RangeEntry<(K2, V), LIM>>,
+#[derive_where(Default; O: Default, S: Default)]
+pub struct L2Heaps<
+    K1: Clone + Eq + Hash,
+    K2: Clone + Eq + Hash,
+    V,
+    O: HeapOrder<K2> = Max,
+    const LIM: usize = 2,
+    S: BuildHasher = RandomState,
+> {
+    map: HashMap<(K1, K2), Index, S>,
+    ranges: HashMap<K1, RangeEntry<(K2, V), LIM>, S>,
     values: IndexList<HeapEntry<K2, V>>,
+    order: O,
+    len: usize,
 }
 
 #[derive_where(Default)]
@@ -27,11 +45,70 @@ struct ExternalEntry {
     i: Index,
     j: Index,
 }
+impl ExternalEntry {
+    fn indices<T>(self, values: &IndexList<T>) -> impl Iterator<Item = Index> {
+        let Self { mut i, j } = self;
+        iter::from_fn(move || {
+            if i.is_none() {
+                return None;
+            }
+            let result = i;
+            if i == j {
+                i = Index::new();
+            } else {
+                i = values.next_index(i);
+            }
+            Some(result)
+        })
+    }
+}
 
-impl<K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V, const LIM: usize> L2Heaps<K1, K2, V, LIM> {
+impl<K1, K2, V, O, const LIM: usize, S> L2Heaps<K1, K2, V, O, LIM, S>
+where
+    K1: Clone + Eq + Hash,
+    K2: Clone + Eq + Hash,
+    O: HeapOrder<K2> + Default,
+    S: BuildHasher + Default,
+{
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<K1, K2, V, O, const LIM: usize, S> L2Heaps<K1, K2, V, O, LIM, S>
+where
+    K1: Clone + Eq + Hash,
+    K2: Clone + Eq + Hash,
+    O: HeapOrder<K2>,
+    S: BuildHasher,
+{
+    pub fn with_order(order: O) -> Self
+    where
+        S: Default,
+    {
+        Self {
+            map: HashMap::default(),
+            ranges: HashMap::default(),
+            values: IndexList::new(),
+            order,
+            len: 0,
+        }
+    }
+    pub fn with_order_and_hasher(order: O, hasher: S) -> Self
+    where
+        S: Clone,
+    {
+        Self {
+            map: HashMap::with_hasher(hasher.clone()),
+            ranges: HashMap::with_hasher(hasher),
+            values: IndexList::new(),
+            order,
+            len: 0,
+        }
+    }
+    pub fn order(&self) -> &O {
+        &self.order
+    }
     pub fn insert(&mut self, k1: K1, k2: K2, v: V) -> Option<V> {
         let mut e = hashmap_tools::or_default(self.ranges.entry(k1));
         let external = match e.get_mut() {
@@ -42,20 +119,28 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V, const LIM: usize> L2Heaps
                     }
                 }
                 let k2v = match inline.try_push((k2, v)) {
-                    Ok(()) => return None,
+                    Ok(()) => {
+                        self.len += 1;
+                        return None;
+                    }
                     Err(cap_err) => cap_err.element(),
                 };
                 let iter = mem::take(inline).into_iter().chain(iter::once(k2v));
                 let k1 = e.key();
-                let mut heap = ExternalHeap::new(k1, &mut self.map, &mut self.values);
+                let mut heap = ExternalHeap::new(k1, &self.order, &mut self.map, &mut self.values);
                 for (k2, v) in iter {
                     heap.insert(k2, v);
                 }
                 heap.into_external().unwrap()
             }
             &mut RangeEntry::External(external) => {
-                let mut heap =
-                    ExternalHeap::from_external(e.key(), external, &mut self.map, &mut self.values);
+                let mut heap = ExternalHeap::from_external(
+                    e.key(),
+                    &self.order,
+                    external,
+                    &mut self.map,
+                    &mut self.values,
+                );
                 if let Some(old_v) = heap.insert(k2, v) {
                     return Some(old_v);
                 }
@@ -63,8 +148,15 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V, const LIM: usize> L2Heaps
             }
         };
         *e.into_mut() = RangeEntry::External(external);
+        self.len += 1;
         None
     }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
     pub fn get(&self, k1: &K1, k2: &K2) -> Option<&V> {
         match self.ranges.get(k1)? {
             RangeEntry::Inline(inline) => inline.iter().find(|(x, _)| x == k2).map(|(_, v)| v),
@@ -88,7 +180,7 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V, const LIM: usize> L2Heaps
             RangeEntry::Inline(inline) => {
                 let (k2, v) = inline
                     .iter()
-                    .max_by(|(kl, _vl), (kr, _vr)| kl.cmp(kr))
+                    .max_by(|(kl, _vl), (kr, _vr)| self.order.cmp(kl, kr))
                     .unwrap();
                 Some((k2, v))
             }
@@ -104,14 +196,20 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V, const LIM: usize> L2Heaps
                         if inline.is_empty() {
                             self.ranges.remove(k1);
                         }
+                        self.len -= 1;
                         return Some(removed);
                     }
                 }
                 None
             }
             RangeEntry::External(external) => {
-                let mut heap =
-                    ExternalHeap::from_external(k1, *external, &mut self.map, &mut self.values);
+                let mut heap = ExternalHeap::from_external(
+                    k1,
+                    &self.order,
+                    *external,
+                    &mut self.map,
+                    &mut self.values,
+                );
                 let removed = heap.remove(k2)?;
                 match heap.into_external() {
                     Some(new_external) => *external = new_external,
@@ -119,8 +217,138 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Ord + Hash, V, const LIM: usize> L2Heaps
                         self.ranges.remove(k1).unwrap();
                     }
                 }
+                self.len -= 1;
                 Some(removed)
             }
         }
     }
+    pub fn pop_max(&mut self, k1: &K1) -> Option<(K2, V)> {
+        let k2 = self.get_max(k1)?.0.clone();
+        let v = self.remove(k1, &k2).unwrap();
+        Some((k2, v))
+    }
+    pub fn peek_n(&self, k1: &K1, n: usize) -> Vec<(&K2, &V)> {
+        match self.ranges.get(k1) {
+            None => Vec::new(),
+            Some(RangeEntry::Inline(_)) => self.iter_sorted(k1).take(n).collect(),
+            Some(RangeEntry::External(external)) => {
+                let mut result = Vec::with_capacity(n);
+                let mut frontier = vec![external.i];
+                while result.len() < n && !frontier.is_empty() {
+                    let (pos, _) = frontier
+                        .iter()
+                        .map(|&ind| self.values.get(ind).unwrap())
+                        .enumerate()
+                        .max_by(|(_, l), (_, r)| self.order.cmp(l.as_refs().0, r.as_refs().0))
+                        .unwrap();
+                    let entry = self.values.get(frontier.swap_remove(pos)).unwrap();
+                    frontier.extend(entry.children());
+                    result.push(entry.as_refs());
+                }
+                result
+            }
+        }
+    }
+    pub fn get_iter<'a>(&'a self, k1: &K1) -> impl Iterator<Item = (&'a K2, &'a V)> {
+        self.ranges
+            .get(k1)
+            .into_iter()
+            .flat_map(|e| self.range_iter(e))
+    }
+    pub fn get_iter_mut<'a>(&'a mut self, k1: &K1) -> impl Iterator<Item = (&'a K2, &'a mut V)> {
+        let entries = match self.ranges.get_mut(k1) {
+            None => Vec::new(),
+            Some(RangeEntry::Inline(inline)) => Vec::from_iter(
+                inline
+                    .iter_mut()
+                    .map(|(k2, v)| (k2 as *const K2, v as *mut V)),
+            ),
+            Some(&mut RangeEntry::External(external)) => {
+                let indices = Vec::from_iter(external.indices(&self.values));
+                Vec::from_iter(indices.into_iter().map(|ind| {
+                    let (k2, v) = self.values.get_mut(ind).unwrap().as_mut_refs();
+                    (k2 as *const K2, v as *mut V)
+                }))
+            }
+        };
+        // Each pointer refers to a distinct entry, all of which stay borrowed for 'a.
+        entries
+            .into_iter()
+            .map(|(k2, v)| unsafe { (&*k2, &mut *v) })
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&K1, &K2, &V)> {
+        self.ranges
+            .iter()
+            .flat_map(|(k1, e)| self.range_iter(e).map(move |(k2, v)| (k1, k2, v)))
+    }
+    pub fn iter_sorted(&self, k1: &K1) -> impl Iterator<Item = (&K2, &V)> {
+        let mut result = Vec::from_iter(self.get_iter(k1));
+        result.sort_by(|(l, _), (r, _)| self.order.cmp(r, l));
+        result.into_iter()
+    }
+    pub fn remove_all(
+        &mut self,
+        k1: &K1,
+    ) -> impl Iterator<Item = (K2, V)> + use<K1, K2, V, O, LIM, S> {
+        let removed = match self.ranges.remove(k1) {
+            None => Vec::new(),
+            Some(RangeEntry::Inline(inline)) => Vec::from_iter(inline),
+            Some(RangeEntry::External(external)) => {
+                let indices = Vec::from_iter(external.indices(&self.values));
+                Vec::from_iter(indices.into_iter().map(|ind| {
+                    let (k2, v) = self.values.remove(ind).unwrap().into_parts();
+                    self.map.remove(&(k1.clone(), k2.clone())).unwrap();
+                    (k2, v)
+                }))
+            }
+        };
+        self.len -= removed.len();
+        removed.into_iter()
+    }
+    pub fn retain(&mut self, mut f: impl FnMut(&K1, &K2, &mut V) -> bool) {
+        let mut removed = Vec::new();
+        for (k1, e) in self.ranges.iter_mut() {
+            match e {
+                RangeEntry::Inline(inline) => {
+                    let before = inline.len();
+                    inline.retain(|(k2, v)| f(k1, k2, v));
+                    self.len -= before - inline.len();
+                }
+                &mut RangeEntry::External(external) => {
+                    for ind in Vec::from_iter(external.indices(&self.values)) {
+                        let (k2, v) = self.values.get_mut(ind).unwrap().as_mut_refs();
+                        if !f(k1, k2, v) {
+                            removed.push((k1.clone(), k2.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        self.ranges
+            .retain(|_, e| !matches!(e, RangeEntry::Inline(inline) if inline.is_empty()));
+        for (k1, k2) in removed {
+            self.remove(&k1, &k2).unwrap();
+        }
+    }
+
+    fn range_iter<'a>(
+        &'a self,
+        e: &'a RangeEntry<(K2, V), LIM>,
+    ) -> impl Iterator<Item = (&'a K2, &'a V)> {
+        let (inline, external) = match e {
+            RangeEntry::Inline(inline) => (Some(inline.iter().map(|(k2, v)| (k2, v))), None),
+            &RangeEntry::External(external) => (
+                None,
+                Some(
+                    external
+                        .indices(&self.values)
+                        .map(|ind| self.values.get(ind).unwrap().as_refs()),
+                ),
+            ),
+        };
+        inline
+            .into_iter()
+            .flatten()
+            .chain(external.into_iter().flatten())
+    }
 }
@@ -0,0 +1,43 @@
+use std::cmp::Ordering;
+
+// The heap keeps the greatest element according to the ordering on top, so `Max`
+// pops the largest key first and `Min` the smallest.
+pub trait HeapOrder<T> {
+    fn cmp(&self, l: &T, r: &T) -> Ordering;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Max;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Min;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ByKey<F>(pub F);
+
+#[derive(Clone, Copy, Debug)]
+pub struct ByCmp<F>(pub F);
+
+impl<T: Ord> HeapOrder<T> for Max {
+    fn cmp(&self, l: &T, r: &T) -> Ordering {
+        l.cmp(r)
+    }
+}
+
+impl<T: Ord> HeapOrder<T> for Min {
+    fn cmp(&self, l: &T, r: &T) -> Ordering {
+        r.cmp(l)
+    }
+}
+
+impl<T, U: Ord, F: Fn(&T) -> U> HeapOrder<T> for ByKey<F> {
+    fn cmp(&self, l: &T, r: &T) -> Ordering {
+        (self.0)(l).cmp(&(self.0)(r))
+    }
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> HeapOrder<T> for ByCmp<F> {
+    fn cmp(&self, l: &T, r: &T) -> Ordering {
+        (self.0)(l, r)
+    }
+}
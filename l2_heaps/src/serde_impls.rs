@@ -0,0 +1,68 @@
+use std::{
+    fmt::{self, Formatter},
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, SeqAccess, Visitor},
+};
+
+use crate::{HeapOrder, L2Heaps};
+
+// Serialized as a flat sequence of (k1, k2, v) entries, independent of the inline/external
+// layout and of the heap order within each group.
+impl<K1, K2, V, O, const LIM: usize, S> Serialize for L2Heaps<K1, K2, V, O, LIM, S>
+where
+    K1: Clone + Eq + Hash + Serialize,
+    K2: Clone + Eq + Hash + Serialize,
+    V: Serialize,
+    O: HeapOrder<K2>,
+    S: BuildHasher,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, K1, K2, V, O, const LIM: usize, S> Deserialize<'de> for L2Heaps<K1, K2, V, O, LIM, S>
+where
+    K1: Clone + Eq + Hash + Deserialize<'de>,
+    K2: Clone + Eq + Hash + Deserialize<'de>,
+    V: Deserialize<'de>,
+    O: HeapOrder<K2> + Default,
+    S: BuildHasher + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(L2HeapsVisitor(PhantomData))
+    }
+}
+
+struct L2HeapsVisitor<M>(PhantomData<fn() -> M>);
+
+impl<'de, K1, K2, V, O, const LIM: usize, S> Visitor<'de>
+    for L2HeapsVisitor<L2Heaps<K1, K2, V, O, LIM, S>>
+where
+    K1: Clone + Eq + Hash + Deserialize<'de>,
+    K2: Clone + Eq + Hash + Deserialize<'de>,
+    V: Deserialize<'de>,
+    O: HeapOrder<K2> + Default,
+    S: BuildHasher + Default,
+{
+    type Value = L2Heaps<K1, K2, V, O, LIM, S>;
+
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("a sequence of (k1, k2, v) entries")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut result = L2Heaps::new();
+        while let Some((k1, k2, v)) = seq.next_element()? {
+            if result.insert(k1, k2, v).is_some() {
+                return Err(de::Error::custom("duplicate L2Heaps entry"));
+            }
+        }
+        Ok(result)
+    }
+}
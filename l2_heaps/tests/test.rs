@@ -2,7 +2,7 @@ use std::collections::{BTreeMap, HashMap};
 
 use rand::{Rng, SeedableRng, rngs::StdRng};
 
-use l2_heaps::L2Heaps;
+use l2_heaps::{ByCmp, ByKey, L2Heaps, Min};
 
 #[test]
 fn test_equivalence() {
@@ -55,3 +55,162 @@ fn test_equivalence() {
         }
     }
 }
+
+#[test]
+fn test_orderings() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(27182);
+
+    let mut mins = L2Heaps::<i32, i32, i32, Min>::new();
+    let mut by_key = L2Heaps::<i32, i32, i32, _>::with_order(ByKey(|x: &i32| x % 7));
+    let mut by_cmp = L2Heaps::<i32, i32, i32, _>::with_order(ByCmp(|l: &i32, r: &i32| r.cmp(l)));
+    let mut hash_map: HashMap<i32, BTreeMap<i32, i32>> = HashMap::new();
+
+    for _ in 0..1000 {
+        let key: i32 = rng.random_range(0..10);
+        let key2: i32 = rng.random_range(0..100);
+        if rng.random_bool(0.3) {
+            let expected = hash_map.get_mut(&key).and_then(|set| set.remove(&key2));
+            assert_eq!(mins.remove(&key, &key2), expected);
+            assert_eq!(by_key.remove(&key, &key2), expected);
+            assert_eq!(by_cmp.remove(&key, &key2), expected);
+        } else {
+            let value: i32 = rng.random_range(0..1000);
+            let expected = hash_map.entry(key).or_default().insert(key2, value);
+            assert_eq!(mins.insert(key, key2, value), expected);
+            assert_eq!(by_key.insert(key, key2, value), expected);
+            assert_eq!(by_cmp.insert(key, key2, value), expected);
+        }
+
+        for k in 0..10 {
+            let ascending = Vec::from_iter(hash_map.get(&k).into_iter().flatten());
+            assert_eq!(mins.get_max(&k), ascending.first().copied());
+            assert_eq!(Vec::from_iter(mins.iter_sorted(&k)), ascending);
+            assert_eq!(
+                mins.peek_n(&k, 3),
+                ascending.iter().copied().take(3).collect::<Vec<_>>()
+            );
+            assert_eq!(Vec::from_iter(by_cmp.iter_sorted(&k)), ascending);
+
+            let mut by_mod = ascending.clone();
+            by_mod.sort_by_key(|&(k2, _)| std::cmp::Reverse(k2 % 7));
+            let expected_keys = Vec::from_iter(by_mod.iter().map(|(k2, _)| *k2 % 7));
+            let peeked = by_key.peek_n(&k, 4);
+            assert_eq!(
+                Vec::from_iter(peeked.iter().map(|(k2, _)| *k2 % 7)),
+                expected_keys.iter().copied().take(4).collect::<Vec<_>>(),
+            );
+            assert_eq!(
+                Vec::from_iter(by_key.iter_sorted(&k).map(|(k2, _)| k2 % 7)),
+                expected_keys,
+            );
+        }
+    }
+
+    for k in 0..10 {
+        let mut popped = Vec::new();
+        while let Some((k2, v)) = mins.pop_max(&k) {
+            popped.push((k2, v));
+        }
+        let expected = Vec::from_iter(hash_map.remove(&k).into_iter().flatten());
+        assert_eq!(popped, expected);
+        assert_eq!(mins.get_max(&k), None);
+    }
+}
+
+#[test]
+fn test_bulk_operations() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(16180);
+
+    let mut l2_heaps = L2Heaps::<i32, i32, i32>::new();
+    let mut hash_map: HashMap<i32, BTreeMap<i32, i32>> = HashMap::new();
+
+    for _ in 0..1000 {
+        let key: i32 = rng.random_range(0..10);
+        match rng.random_range(0..20) {
+            0 => {
+                let removed: BTreeMap<_, _> = l2_heaps.remove_all(&key).collect();
+                assert_eq!(removed, hash_map.remove(&key).unwrap_or_default());
+            }
+            1 => {
+                let modulus = rng.random_range(2..5);
+                l2_heaps.retain(|_, &k2, v| {
+                    *v += 1;
+                    k2 % modulus != 0
+                });
+                for set in hash_map.values_mut() {
+                    set.retain(|&k2, v| {
+                        *v += 1;
+                        k2 % modulus != 0
+                    });
+                }
+                hash_map.retain(|_, set| !set.is_empty());
+            }
+            2 => {
+                for (_, v) in l2_heaps.get_iter_mut(&key) {
+                    *v *= 2;
+                }
+                for v in hash_map
+                    .get_mut(&key)
+                    .into_iter()
+                    .flat_map(|s| s.values_mut())
+                {
+                    *v *= 2;
+                }
+            }
+            3..10 => {
+                let key2: i32 = rng.random_range(0..20);
+                let heaps_removed = l2_heaps.remove(&key, &key2);
+                let map_removed = hash_map.get_mut(&key).and_then(|s| s.remove(&key2));
+                hash_map.retain(|_, set| !set.is_empty());
+                assert_eq!(heaps_removed, map_removed);
+            }
+            _ => {
+                let key2: i32 = rng.random_range(0..20);
+                let value: i32 = rng.random_range(0..1000);
+                let heaps_inserted = l2_heaps.insert(key, key2, value);
+                let map_inserted = hash_map.entry(key).or_default().insert(key2, value);
+                assert_eq!(heaps_inserted, map_inserted);
+            }
+        }
+
+        let expected: BTreeMap<_, _> = hash_map
+            .iter()
+            .flat_map(|(&k1, s)| s.iter().map(move |(&k2, &v)| ((k1, k2), v)))
+            .collect();
+        assert_eq!(l2_heaps.len(), expected.len());
+        assert_eq!(
+            l2_heaps
+                .iter()
+                .map(|(&k1, &k2, &v)| ((k1, k2), v))
+                .collect::<BTreeMap<_, _>>(),
+            expected
+        );
+        for k in 0..10 {
+            assert_eq!(
+                l2_heaps.get_max(&k),
+                hash_map.get(&k).and_then(|set| set.iter().next_back()),
+            );
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_roundtrip() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(2718);
+
+    let mut l2_heaps = L2Heaps::<i32, i32, i32, Min>::new();
+    for _ in 0..500 {
+        l2_heaps.insert(
+            rng.random_range(0..20),
+            rng.random_range(0..10),
+            rng.random(),
+        );
+    }
+    let json = serde_json::to_string(&l2_heaps).unwrap();
+    let decoded: L2Heaps<i32, i32, i32, Min> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.len(), l2_heaps.len());
+    for k1 in 0..20 {
+        assert!(decoded.iter_sorted(&k1).eq(l2_heaps.iter_sorted(&k1)));
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     collections::{HashMap, hash_map},
-    hash::Hash,
+    hash::{BuildHasher, Hash, RandomState},
     iter, mem,
 };
 
@@ -9,11 +9,24 @@ use derive_where::derive_where;
 use index_list::{Index, IndexList};
 use itertools::Either;
 
-#[derive_where(Default)]
-pub struct L2Map<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize = 2> {
-    map: HashMap<(K1, K2), Index>,
-    ranges: HashMap<K1, RangeEntry<(K2, V), LIM>>,
+pub use self::ordered::OrderedL2Map;
+
+mod ordered;
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+#[derive_where(Default; S: Default)]
+pub struct L2Map<
+    K1: Clone + Eq + Hash,
+    K2: Clone + Eq + Hash,
+    V,
+    const LIM: usize = 2,
+    S: BuildHasher = RandomState,
+> {
+    map: HashMap<(K1, K2), Index, S>,
+    ranges: HashMap<K1, RangeEntry<(K2, V), LIM>, S>,
     values: IndexList<(K2, V)>,
+    len: usize,
 }
 
 #[derive_where(Default)]
@@ -27,15 +40,16 @@ enum RangeEntry<T, const LIM: usize> {
 struct ExternalEntry {
     i: Index,
     j: Index,
+    len: usize,
 }
 impl ExternalEntry {
-    fn iter<T>(self, values: &IndexList<T>) -> impl Iterator<Item = &T> {
-        let Self { mut i, j } = self;
+    fn indices<T>(self, values: &IndexList<T>) -> impl Iterator<Item = Index> {
+        let Self { mut i, j, .. } = self;
         iter::from_fn(move || {
             if i.is_none() {
                 return None;
             }
-            let result = values.get(i).unwrap();
+            let result = i;
             if i == j {
                 i = Index::new();
             } else {
@@ -44,12 +58,33 @@ impl ExternalEntry {
             Some(result)
         })
     }
+    fn iter<T>(self, values: &IndexList<T>) -> impl Iterator<Item = &T> {
+        self.indices(values).map(|i| values.get(i).unwrap())
+    }
 }
 
-impl<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize> L2Map<K1, K2, V, LIM> {
+impl<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize, S: BuildHasher + Default>
+    L2Map<K1, K2, V, LIM, S>
+{
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize, S: BuildHasher>
+    L2Map<K1, K2, V, LIM, S>
+{
+    pub fn with_hasher(hasher: S) -> Self
+    where
+        S: Clone,
+    {
+        Self {
+            map: HashMap::with_hasher(hasher.clone()),
+            ranges: HashMap::with_hasher(hasher),
+            values: IndexList::new(),
+            len: 0,
+        }
+    }
     pub fn insert(&mut self, k1: K1, k2: K2, v: V) -> Option<V> {
         let mut e = hashmap_tools::or_default(self.ranges.entry(k1));
         let external = match e.get_mut() {
@@ -60,7 +95,10 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize> L2Map<K1
                     }
                 }
                 let (k2, v) = match inline.try_push((k2, v)) {
-                    Ok(()) => return None,
+                    Ok(()) => {
+                        self.len += 1;
+                        return None;
+                    }
                     Err(cap_err) => cap_err.element(),
                 };
                 let mut iter = mem::take(inline).into_iter().chain(iter::once((k2, v)));
@@ -73,7 +111,7 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize> L2Map<K1
                     j = self.values.insert_after(j, (k2.clone(), v));
                     self.map.insert((k1.clone(), k2), j);
                 }
-                ExternalEntry { i, j }
+                ExternalEntry { i, j, len: LIM + 1 }
             }
             &mut RangeEntry::External(mut external) => {
                 match self.map.entry((e.key().clone(), k2)) {
@@ -84,6 +122,7 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize> L2Map<K1
                     hash_map::Entry::Vacant(e) => {
                         let (_k1, k2) = e.key();
                         external.j = self.values.insert_after(external.j, (k2.clone(), v));
+                        external.len += 1;
                         e.insert(external.j);
                         external
                     }
@@ -91,13 +130,85 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize> L2Map<K1
             }
         };
         *e.into_mut() = RangeEntry::External(external);
+        self.len += 1;
         None
     }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
     pub fn get_iter<'a>(&'a self, k: &'a K1) -> impl Iterator<Item = &'a (K2, V)> {
-        self.ranges.get(k).into_iter().flat_map(|e| match e {
-            RangeEntry::Inline(inline) => Either::Left(inline.iter()),
-            RangeEntry::External(external) => Either::Right(external.iter(&self.values)),
-        })
+        self.ranges
+            .get(k)
+            .into_iter()
+            .flat_map(|e| range_iter(e, &self.values))
+    }
+    pub fn get_iter_mut<'a>(&'a mut self, k: &K1) -> impl Iterator<Item = (&'a K2, &'a mut V)> {
+        let entries = match self.ranges.get_mut(k) {
+            None => Vec::new(),
+            Some(RangeEntry::Inline(inline)) => Vec::from_iter(
+                inline
+                    .iter_mut()
+                    .map(|(k2, v)| (k2 as *const K2, v as *mut V)),
+            ),
+            Some(&mut RangeEntry::External(external)) => {
+                let indices = Vec::from_iter(external.indices(&self.values));
+                Vec::from_iter(indices.into_iter().map(|i| {
+                    let (k2, v) = self.values.get_mut(i).unwrap();
+                    (k2 as *const K2, v as *mut V)
+                }))
+            }
+        };
+        // Each pointer refers to a distinct entry, all of which stay borrowed for 'a.
+        entries
+            .into_iter()
+            .map(|(k2, v)| unsafe { (&*k2, &mut *v) })
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&K1, &K2, &V)> {
+        self.ranges
+            .iter()
+            .flat_map(|(k1, e)| range_iter(e, &self.values).map(move |(k2, v)| (k1, k2, v)))
+    }
+    pub fn remove_all(
+        &mut self,
+        k1: &K1,
+    ) -> impl Iterator<Item = (K2, V)> + use<K1, K2, V, LIM, S> {
+        let removed = match self.ranges.remove(k1) {
+            None => Vec::new(),
+            Some(RangeEntry::Inline(inline)) => Vec::from_iter(inline),
+            Some(RangeEntry::External(external)) => {
+                take_external(&mut self.map, &mut self.values, k1, external)
+            }
+        };
+        self.len -= removed.len();
+        removed.into_iter()
+    }
+    pub fn retain(&mut self, mut f: impl FnMut(&K1, &K2, &mut V) -> bool) {
+        let mut removed = Vec::new();
+        for (k1, e) in self.ranges.iter_mut() {
+            match e {
+                RangeEntry::Inline(inline) => {
+                    let before = inline.len();
+                    inline.retain(|(k2, v)| f(k1, k2, v));
+                    self.len -= before - inline.len();
+                }
+                &mut RangeEntry::External(external) => {
+                    for i in Vec::from_iter(external.indices(&self.values)) {
+                        let (k2, v) = self.values.get_mut(i).unwrap();
+                        if !f(k1, k2, v) {
+                            removed.push((k1.clone(), k2.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        self.ranges
+            .retain(|_, e| !matches!(e, RangeEntry::Inline(inline) if inline.is_empty()));
+        for (k1, k2) in removed {
+            self.remove(&k1, &k2).unwrap();
+        }
     }
     pub fn get(&self, k1: &K1, k2: &K2) -> Option<&V> {
         let range = self.ranges.get(k1)?;
@@ -144,6 +255,10 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize> L2Map<K1
                 for (i, (x, _v)) in inline.iter().enumerate() {
                     if x == k2 {
                         let (_, v) = inline.remove(i);
+                        if inline.is_empty() {
+                            self.ranges.remove(k1);
+                        }
+                        self.len -= 1;
                         return Some(v);
                     }
                 }
@@ -151,9 +266,9 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize> L2Map<K1
             }
             RangeEntry::External(external) => {
                 let i = self.map.remove(&(k1.clone(), k2.clone()))?;
-                if external.i == external.j {
-                    assert!(i == external.i);
-                    self.ranges.remove(k1);
+                external.len -= 1;
+                if external.len == 0 {
+                    assert!(i == external.i && i == external.j);
                 } else if i == external.i {
                     external.i = self.values.next_index(i);
                 } else if i == external.j {
@@ -161,8 +276,73 @@ impl<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, const LIM: usize> L2Map<K1
                 }
                 let (removed_k2, v) = self.values.remove(i).unwrap();
                 assert!(removed_k2 == *k2);
+                self.len -= 1;
+                if external.len == 0 {
+                    self.ranges.remove(k1);
+                } else if external.len < LIM {
+                    let external = *external;
+                    let remaining = take_external(&mut self.map, &mut self.values, k1, external);
+                    *range = RangeEntry::Inline(ArrayVec::from_iter(remaining));
+                }
                 Some(v)
             }
         }
     }
+    pub fn shrink_to_fit(&mut self) {
+        let mut old_values = mem::take(&mut self.values);
+        self.values = IndexList::with_capacity(old_values.len());
+        for (k1, e) in self.ranges.iter_mut() {
+            let RangeEntry::External(external) = e else {
+                continue;
+            };
+            let indices = Vec::from_iter(external.indices(&old_values));
+            let mut compacted: Option<ExternalEntry> = None;
+            for ind in indices {
+                let (k2, v) = old_values.remove(ind).unwrap();
+                let new_ind = self.values.insert_last((k2.clone(), v));
+                *self.map.get_mut(&(k1.clone(), k2)).unwrap() = new_ind;
+                match &mut compacted {
+                    None => {
+                        compacted = Some(ExternalEntry {
+                            i: new_ind,
+                            j: new_ind,
+                            len: 1,
+                        })
+                    }
+                    Some(compacted) => {
+                        compacted.j = new_ind;
+                        compacted.len += 1;
+                    }
+                }
+            }
+            *external = compacted.unwrap();
+        }
+        assert!(old_values.is_empty());
+        self.map.shrink_to_fit();
+        self.ranges.shrink_to_fit();
+    }
+}
+
+fn take_external<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, S: BuildHasher>(
+    map: &mut HashMap<(K1, K2), Index, S>,
+    values: &mut IndexList<(K2, V)>,
+    k1: &K1,
+    external: ExternalEntry,
+) -> Vec<(K2, V)> {
+    let indices = Vec::from_iter(external.indices(values));
+    Vec::from_iter(indices.into_iter().map(|i| {
+        let (k2, v) = values.remove(i).unwrap();
+        map.remove(&(k1.clone(), k2.clone())).unwrap();
+        (k2, v)
+    }))
+}
+
+fn range_iter<'a, T, const LIM: usize>(
+    e: &'a RangeEntry<T, LIM>,
+    values: &'a IndexList<T>,
+) -> impl Iterator<Item = &'a T> {
+    match e {
+        RangeEntry::Inline(inline) => Either::Left(inline.iter()),
+        RangeEntry::External(external) => Either::Right(external.iter(values)),
+    }
 }
@@ -0,0 +1,74 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hash, RandomState},
+    ops::RangeBounds,
+};
+
+use derive_where::derive_where;
+
+#[derive_where(Default; S: Default)]
+pub struct OrderedL2Map<K1: Eq + Hash, K2: Ord, V, S: BuildHasher = RandomState> {
+    ranges: HashMap<K1, BTreeMap<K2, V>, S>,
+}
+
+impl<K1: Eq + Hash, K2: Ord, V, S: BuildHasher + Default> OrderedL2Map<K1, K2, V, S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K1: Eq + Hash, K2: Ord, V, S: BuildHasher> OrderedL2Map<K1, K2, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            ranges: HashMap::with_hasher(hasher),
+        }
+    }
+    pub fn insert(&mut self, k1: K1, k2: K2, v: V) -> Option<V> {
+        self.ranges.entry(k1).or_default().insert(k2, v)
+    }
+    pub fn get(&self, k1: &K1, k2: &K2) -> Option<&V> {
+        self.ranges.get(k1)?.get(k2)
+    }
+    pub fn get_mut(&mut self, k1: &K1, k2: &K2) -> Option<&mut V> {
+        self.ranges.get_mut(k1)?.get_mut(k2)
+    }
+    pub fn remove(&mut self, k1: &K1, k2: &K2) -> Option<V> {
+        let range = self.ranges.get_mut(k1)?;
+        let removed = range.remove(k2)?;
+        if range.is_empty() {
+            self.ranges.remove(k1);
+        }
+        Some(removed)
+    }
+    pub fn get_iter<'a>(
+        &'a self,
+        k1: &K1,
+    ) -> impl DoubleEndedIterator<Item = (&'a K2, &'a V)> + use<'a, K1, K2, V, S> {
+        self.ranges.get(k1).into_iter().flatten()
+    }
+    pub fn range<'a, R: RangeBounds<K2>>(
+        &'a self,
+        k1: &K1,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (&'a K2, &'a V)> + use<'a, K1, K2, V, S, R> {
+        self.ranges
+            .get(k1)
+            .map(|values| values.range(range))
+            .into_iter()
+            .flatten()
+    }
+    pub fn first_n<'a>(
+        &'a self,
+        k1: &K1,
+        n: usize,
+    ) -> impl Iterator<Item = (&'a K2, &'a V)> + use<'a, K1, K2, V, S> {
+        self.get_iter(k1).take(n)
+    }
+    pub fn last_n<'a>(
+        &'a self,
+        k1: &K1,
+        n: usize,
+    ) -> impl Iterator<Item = (&'a K2, &'a V)> + use<'a, K1, K2, V, S> {
+        self.get_iter(k1).rev().take(n)
+    }
+}
@@ -1,6 +1,6 @@
-use l2_map::L2Map;
+use l2_map::{L2Map, OrderedL2Map};
 use rand::{Rng, SeedableRng, rngs::StdRng};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[test]
 fn test_l2map_equivalence() {
@@ -55,3 +55,150 @@ fn test_l2map_equivalence() {
         assert_eq!(l2_iter, hash_map_iter);
     }
 }
+
+#[test]
+fn test_ordered_l2map_equivalence() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(31416);
+
+    let mut l2_map: OrderedL2Map<i32, i32, i32> = OrderedL2Map::new();
+    let mut hash_map: HashMap<i32, BTreeMap<i32, i32>> = HashMap::new();
+
+    for _ in 0..1000 {
+        let k1: i32 = rng.random_range(0..10);
+        let k2: i32 = rng.random_range(0..20);
+        let v: i32 = rng.random_range(0..100);
+
+        if rng.random_bool(0.3) {
+            let l2_map_removed = l2_map.remove(&k1, &k2);
+            let hash_map_removed = match hash_map.get_mut(&k1) {
+                Some(inner_map) => {
+                    let result = inner_map.remove(&k2);
+                    if inner_map.is_empty() {
+                        hash_map.remove(&k1);
+                    }
+                    result
+                }
+                None => None,
+            };
+            assert_eq!(l2_map_removed, hash_map_removed);
+        } else {
+            let l2_map_replaced = l2_map.insert(k1, k2, v);
+            let hash_map_replaced = hash_map.entry(k1).or_default().insert(k2, v);
+            assert_eq!(l2_map_replaced, hash_map_replaced);
+        }
+
+        let inner_map = hash_map.get(&k1);
+        assert_eq!(
+            Vec::from_iter(l2_map.range(&k1, 5..15)),
+            inner_map.map_or(Vec::new(), |inner_map| Vec::from_iter(
+                inner_map.range(5..15)
+            ))
+        );
+        assert_eq!(
+            Vec::from_iter(l2_map.first_n(&k1, 3)),
+            inner_map.map_or(Vec::new(), |inner_map| Vec::from_iter(
+                inner_map.iter().take(3)
+            ))
+        );
+        assert_eq!(
+            Vec::from_iter(l2_map.last_n(&k1, 3)),
+            inner_map.map_or(Vec::new(), |inner_map| {
+                Vec::from_iter(inner_map.iter().rev().take(3))
+            })
+        );
+    }
+}
+
+#[test]
+fn test_l2map_bulk_operations() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(27182);
+
+    let mut l2_map: L2Map<i32, i32, i32> = L2Map::new();
+    let mut hash_map: HashMap<i32, HashMap<i32, i32>> = HashMap::new();
+
+    for _ in 0..1000 {
+        let k1: i32 = rng.random_range(0..10);
+        match rng.random_range(0..20) {
+            0 => {
+                let removed: HashMap<_, _> = l2_map.remove_all(&k1).collect();
+                assert_eq!(removed, hash_map.remove(&k1).unwrap_or_default());
+            }
+            1 => {
+                let modulus = rng.random_range(2..5);
+                l2_map.retain(|_, &k2, v| {
+                    *v += 1;
+                    k2 % modulus != 0
+                });
+                for inner_map in hash_map.values_mut() {
+                    inner_map.retain(|&k2, v| {
+                        *v += 1;
+                        k2 % modulus != 0
+                    });
+                }
+                hash_map.retain(|_, inner_map| !inner_map.is_empty());
+            }
+            2 => {
+                for (_, v) in l2_map.get_iter_mut(&k1) {
+                    *v *= 2;
+                }
+                for v in hash_map
+                    .get_mut(&k1)
+                    .into_iter()
+                    .flat_map(|m| m.values_mut())
+                {
+                    *v *= 2;
+                }
+            }
+            3 => l2_map.shrink_to_fit(),
+            4..10 => {
+                let k2: i32 = rng.random_range(0..10);
+                let l2_map_removed = l2_map.remove(&k1, &k2);
+                let hash_map_removed = hash_map.get_mut(&k1).and_then(|m| m.remove(&k2));
+                hash_map.retain(|_, inner_map| !inner_map.is_empty());
+                assert_eq!(l2_map_removed, hash_map_removed);
+            }
+            _ => {
+                let k2: i32 = rng.random_range(0..10);
+                let v: i32 = rng.random_range(0..100);
+                let l2_map_replaced = l2_map.insert(k1, k2, v);
+                let hash_map_replaced = hash_map.entry(k1).or_default().insert(k2, v);
+                assert_eq!(l2_map_replaced, hash_map_replaced);
+            }
+        }
+
+        let expected: HashSet<_> = hash_map
+            .iter()
+            .flat_map(|(&k1, m)| m.iter().map(move |(&k2, &v)| (k1, k2, v)))
+            .collect();
+        assert_eq!(l2_map.len(), expected.len());
+        assert_eq!(
+            l2_map
+                .iter()
+                .map(|(&k1, &k2, &v)| (k1, k2, v))
+                .collect::<HashSet<_>>(),
+            expected
+        );
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_l2map_serde_roundtrip() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(2718);
+
+    let mut l2_map: L2Map<i32, i32, i32> = L2Map::new();
+    for _ in 0..500 {
+        l2_map.insert(
+            rng.random_range(0..20),
+            rng.random_range(0..10),
+            rng.random(),
+        );
+    }
+    let json = serde_json::to_string(&l2_map).unwrap();
+    let decoded: L2Map<i32, i32, i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.len(), l2_map.len());
+    for (k1, k2, v) in l2_map.iter() {
+        assert_eq!(decoded.get(k1, k2), Some(v));
+    }
+    assert!(serde_json::from_str::<L2Map<i32, i32, i32>>("[[1,2,3],[1,2,4]]").is_err());
+}
@@ -1,6 +1,6 @@
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash, RandomState};
 
-use relation_pipeline::{InputRelation, Relation, RelationalOp};
+use relation_pipeline::{ContextMismatch, IndexedOutput, InputRelation, Relation, RelationalOp};
 
 use crate::{
     FirstOccurrencesInput, Input, InterruptId, Output,
@@ -16,20 +16,29 @@ pub struct Context<C> {
     inputs: Vec<Box<dyn IsTrackedInput>>,
 }
 
-pub type CreationContext = Context<relation_pipeline::CreationContext>;
+pub type CreationContext<S = RandomState> = Context<relation_pipeline::CreationContext<S>>;
 pub type ExecutionContext = Context<relation_pipeline::ExecutionContext>;
 
 impl CreationContext {
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<S: BuildHasher + Default + 'static> CreationContext<S> {
+    #[track_caller]
+    pub fn deterministic(mut self) -> Self {
+        self.inner = self.inner.deterministic();
+        self
+    }
 
+    #[allow(clippy::type_complexity)]
     pub fn new_first_occurrences_input<
         K: Eq + Hash + Clone + 'static,
         V: Ord + Hash + Clone + 'static,
     >(
         &mut self,
-    ) -> (FirstOccurrencesInput<K, V>, InputRelation<(K, V)>) {
+    ) -> (FirstOccurrencesInput<K, V, S>, InputRelation<(K, V), S>) {
         let (inner, rel) = self.inner.new_input();
         let input = FirstOccurrencesInput::new(inner);
         self.inputs.push(Box::new(input.clone()));
@@ -38,46 +47,81 @@ impl CreationContext {
 
     pub fn new_frameless_input<T: Eq + Hash + Clone + 'static>(
         &mut self,
-    ) -> (FramelessInput<T>, InputRelation<T>) {
+    ) -> (FramelessInput<T, S>, InputRelation<T, S>) {
         let (inp, rel) = self.inner.new_input();
         (FramelessInput::new(inp), rel)
     }
 
     pub fn new_input<T: Eq + Hash + Clone + 'static>(
         &mut self,
-    ) -> (Input<T>, Relation<T, impl RelationalOp<T = T> + use<T>>) {
+    ) -> (
+        Input<T, S>,
+        Relation<T, impl RelationalOp<T = T> + use<T, S>, S>,
+    ) {
         let (inner, rel) = self.new_first_occurrences_input::<T, ()>();
         (Input(inner), rel.fsts())
     }
 
+    #[track_caller]
     pub fn set_first_occurrences_feedback<
         K: Eq + Hash + Clone + 'static,
         V: Ord + Hash + Clone + 'static,
     >(
         &mut self,
-        output: Relation<(K, V), impl RelationalOp<T = (K, V)> + 'static>,
-        input: FirstOccurrencesInput<K, V>,
+        output: Relation<(K, V), impl RelationalOp<T = (K, V)> + 'static, S>,
+        input: FirstOccurrencesInput<K, V, S>,
     ) {
-        assert!(self.inner.matches_relation(&output));
-        assert!(input.matches_context(&self.inner));
-        self.feeders
-            .push(Box::new((self.inner.output(output), input)));
+        self.try_set_first_occurrences_feedback(output, input)
+            .unwrap()
+    }
+
+    pub fn try_set_first_occurrences_feedback<
+        K: Eq + Hash + Clone + 'static,
+        V: Ord + Hash + Clone + 'static,
+    >(
+        &mut self,
+        output: Relation<(K, V), impl RelationalOp<T = (K, V)> + 'static, S>,
+        input: FirstOccurrencesInput<K, V, S>,
+    ) -> Result<(), ContextMismatch> {
+        input.check_context("set_feedback", &self.inner)?;
+        let output = self.inner.try_output(output)?;
+        self.feeders.push(Box::new((output, input)));
+        Ok(())
     }
 
-    pub fn set_feedback<I: FeedbackableFrom<O>, O>(&mut self, output: O, input: I) {
-        input.feedback_from(self, output);
+    #[track_caller]
+    pub fn set_feedback<I: FeedbackableFrom<O, S>, O>(&mut self, output: O, input: I) {
+        self.try_set_feedback(output, input).unwrap()
+    }
+
+    pub fn try_set_feedback<I: FeedbackableFrom<O, S>, O>(
+        &mut self,
+        output: O,
+        input: I,
+    ) -> Result<(), ContextMismatch> {
+        input.feedback_from(self, output)
     }
 
+    #[track_caller]
     pub fn set_interrupt<T: Eq + Hash + Clone + 'static, Op: RelationalOp<T = T> + 'static>(
         &mut self,
-        relation: Relation<T, Op>,
+        relation: Relation<T, Op, S>,
         interrupt_id: InterruptId,
     ) {
-        assert!(self.inner.matches_relation(&relation));
+        self.try_set_interrupt(relation, interrupt_id).unwrap()
+    }
+
+    pub fn try_set_interrupt<T: Eq + Hash + Clone + 'static, Op: RelationalOp<T = T> + 'static>(
+        &mut self,
+        relation: Relation<T, Op, S>,
+        interrupt_id: InterruptId,
+    ) -> Result<(), ContextMismatch> {
+        let output = self.try_output(relation)?;
         self.feeders.push(Box::new(Interrupter {
-            output: self.output(relation),
+            output,
             interrupt_id,
         }));
+        Ok(())
     }
 
     pub fn begin(self) -> ExecutionContext {
@@ -89,14 +133,32 @@ impl CreationContext {
     }
 
     #[must_use]
+    #[track_caller]
     pub fn output<T: Eq + Hash + Clone, Op: RelationalOp<T = T>>(
         &self,
-        relation: Relation<T, Op>,
-    ) -> Output<T, Op::Unconsolidated> {
-        Output::new(self.inner.output(relation.unconsolidate()))
+        relation: Relation<T, Op, S>,
+    ) -> Output<T, Op::Unconsolidated, S> {
+        self.try_output(relation).unwrap()
     }
 
-    pub fn constant<T>(&self, values: impl IntoIterator<Item = T>) -> InputRelation<T> {
+    pub fn try_output<T: Eq + Hash + Clone, Op: RelationalOp<T = T>>(
+        &self,
+        relation: Relation<T, Op, S>,
+    ) -> Result<Output<T, Op::Unconsolidated, S>, ContextMismatch> {
+        Ok(Output::new(
+            self.inner.try_output(relation.unconsolidate())?,
+        ))
+    }
+
+    #[must_use]
+    pub fn indexed_output<K: Ord, V: Ord, Op: RelationalOp<T = (K, V)>>(
+        &self,
+        relation: Relation<(K, V), Op, S>,
+    ) -> IndexedOutput<K, V, Op::Unconsolidated, S> {
+        self.inner.indexed_output(relation.unconsolidate())
+    }
+
+    pub fn constant<T>(&self, values: impl IntoIterator<Item = T>) -> InputRelation<T, S> {
         self.inner.constant(values.into_iter().map(|x| (x, 1)))
     }
 }
@@ -149,37 +211,59 @@ impl ExecutionContext {
     }
 }
 
-pub trait FeedbackableFrom<O> {
-    fn feedback_from(self, context: &mut CreationContext, output: O);
+pub trait FeedbackableFrom<O, S = RandomState> {
+    fn feedback_from(
+        self,
+        context: &mut CreationContext<S>,
+        output: O,
+    ) -> Result<(), ContextMismatch>;
 }
 
 impl<
     K: Eq + Hash + Clone + 'static,
     V: Ord + Hash + Clone + 'static,
     Op: RelationalOp<T = (K, V)> + 'static,
-> FeedbackableFrom<Relation<(K, V), Op>> for FirstOccurrencesInput<K, V>
+    S: BuildHasher + Default + 'static,
+> FeedbackableFrom<Relation<(K, V), Op, S>, S> for FirstOccurrencesInput<K, V, S>
 {
-    fn feedback_from(self, context: &mut CreationContext, output: Relation<(K, V), Op>) {
-        context.set_first_occurrences_feedback(output, self)
+    fn feedback_from(
+        self,
+        context: &mut CreationContext<S>,
+        output: Relation<(K, V), Op, S>,
+    ) -> Result<(), ContextMismatch> {
+        context.try_set_first_occurrences_feedback(output, self)
     }
 }
 
-impl<T: Eq + Hash + Clone + 'static, Op: RelationalOp<T = T> + 'static>
-    FeedbackableFrom<Relation<T, Op>> for Input<T>
+impl<
+    T: Eq + Hash + Clone + 'static,
+    Op: RelationalOp<T = T> + 'static,
+    S: BuildHasher + Default + 'static,
+> FeedbackableFrom<Relation<T, Op, S>, S> for Input<T, S>
 {
-    fn feedback_from(self, context: &mut CreationContext, output: Relation<T, Op>) {
-        context.set_first_occurrences_feedback(output.map_h(|x| (x, ())), self.0)
+    fn feedback_from(
+        self,
+        context: &mut CreationContext<S>,
+        output: Relation<T, Op, S>,
+    ) -> Result<(), ContextMismatch> {
+        context.try_set_first_occurrences_feedback(output.map_h(|x| (x, ())), self.0)
     }
 }
 
-impl<T: Eq + Hash + Clone + 'static, Op: RelationalOp<T = T> + 'static>
-    FeedbackableFrom<Relation<T, Op>> for FramelessInput<T>
+impl<
+    T: Eq + Hash + Clone + 'static,
+    Op: RelationalOp<T = T> + 'static,
+    S: BuildHasher + Default + 'static,
+> FeedbackableFrom<Relation<T, Op, S>, S> for FramelessInput<T, S>
 {
-    fn feedback_from(self, context: &mut CreationContext, output: Relation<T, Op>) {
-        assert!(context.inner.matches_relation(&output));
-        assert!(self.matches_context(&context.inner));
-        context
-            .feeders
-            .push(Box::new((context.inner.output(output), self)));
+    fn feedback_from(
+        self,
+        context: &mut CreationContext<S>,
+        output: Relation<T, Op, S>,
+    ) -> Result<(), ContextMismatch> {
+        self.check_context("set_feedback", &context.inner)?;
+        let output = context.inner.try_output(output)?;
+        context.feeders.push(Box::new((output, self)));
+        Ok(())
     }
 }
@@ -1,4 +1,4 @@
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
 
 use relation_pipeline::RelationalOp;
 
@@ -16,11 +16,16 @@ pub(crate) trait Feeder {
     fn feed(&mut self) -> FeedResult;
 }
 
-impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone, Op: RelationalOp<T = (K, V)>> Feeder
+impl<K, V, Op, S> Feeder
     for (
-        relation_pipeline::Output<(K, V), Op>,
-        FirstOccurrencesInput<K, V>,
+        relation_pipeline::Output<(K, V), Op, S>,
+        FirstOccurrencesInput<K, V, S>,
     )
+where
+    K: Eq + Hash + Clone,
+    V: Ord + Hash + Clone,
+    Op: RelationalOp<T = (K, V)>,
+    S: BuildHasher,
 {
     fn feed(&mut self) -> FeedResult {
         let any_sent = self.1.insert_all(&mut self.0);
@@ -32,8 +37,8 @@ impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone, Op: RelationalOp<T = (K, V)>>
     }
 }
 
-impl<T: Eq + Hash + Clone, Op: RelationalOp<T = T>> Feeder
-    for (relation_pipeline::Output<T, Op>, FramelessInput<T>)
+impl<T: Eq + Hash + Clone, Op: RelationalOp<T = T>, S: BuildHasher> Feeder
+    for (relation_pipeline::Output<T, Op, S>, FramelessInput<T, S>)
 {
     fn feed(&mut self) -> FeedResult {
         let any_sent = self.1.insert_all(&mut self.0);
@@ -45,12 +50,14 @@ impl<T: Eq + Hash + Clone, Op: RelationalOp<T = T>> Feeder
     }
 }
 
-pub(crate) struct Interrupter<T, Op: RelationalOp<T = T>> {
-    pub(crate) output: Output<T, Op>,
+pub(crate) struct Interrupter<T, Op: RelationalOp<T = T>, S> {
+    pub(crate) output: Output<T, Op, S>,
     pub(crate) interrupt_id: InterruptId,
 }
 
-impl<T: Eq + Hash + Clone, Op: RelationalOp<T = T>> Feeder for Interrupter<T, Op> {
+impl<T: Eq + Hash + Clone, Op: RelationalOp<T = T>, S: BuildHasher> Feeder
+    for Interrupter<T, Op, S>
+{
     fn feed(&mut self) -> FeedResult {
         if self.output.is_empty() {
             FeedResult::Unchanged
@@ -1,27 +1,31 @@
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
-use std::hash::Hash;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash, RandomState};
 use std::rc::Rc;
 
 use derive_where::derive_where;
+use indexmap::IndexMap;
 
-use relation_pipeline::RelationalOp;
+use relation_pipeline::{ContextMismatch, RelationalOp};
 
 #[derive_where(Clone)]
-pub struct FramelessInput<T>(Rc<RefCell<FramelessInputInner<T>>>);
+pub struct FramelessInput<T, S = RandomState>(Rc<RefCell<FramelessInputInner<T, S>>>);
 
-struct FramelessInputInner<T> {
+struct FramelessInputInner<T, S> {
     input: relation_pipeline::Input<T>,
-    sent: HashSet<T>,
-    pending_counts: HashMap<T, i64>,
+    sent: HashSet<T, S>,
+    pending_counts: IndexMap<T, i64, S>,
 }
 
-impl<T: Eq + Hash + Clone> FramelessInput<T> {
-    pub(crate) fn new(input: relation_pipeline::Input<T>) -> Self {
+impl<T: Eq + Hash + Clone, S: BuildHasher> FramelessInput<T, S> {
+    pub(crate) fn new(input: relation_pipeline::Input<T>) -> Self
+    where
+        S: Default,
+    {
         Self(Rc::new(RefCell::new(FramelessInputInner {
             input,
-            sent: HashSet::new(),
-            pending_counts: HashMap::new(),
+            sent: HashSet::default(),
+            pending_counts: IndexMap::default(),
         })))
     }
 
@@ -29,32 +33,50 @@ impl<T: Eq + Hash + Clone> FramelessInput<T> {
         self.0.borrow_mut().insert(value)
     }
 
+    pub fn extend(&self, values: impl IntoIterator<Item = T>) {
+        self.0.borrow_mut().extend(values)
+    }
+
     pub(crate) fn insert_all(
         &self,
-        output: &mut relation_pipeline::Output<T, impl RelationalOp<T = T>>,
+        output: &mut relation_pipeline::Output<T, impl RelationalOp<T = T>, S>,
     ) -> bool {
         self.0.borrow_mut().insert_all(output)
     }
 
-    pub(crate) fn matches_context(&self, context: &relation_pipeline::CreationContext) -> bool {
-        context.matches_input(&self.0.borrow().input)
+    pub(crate) fn check_context(
+        &self,
+        operation: &'static str,
+        context: &relation_pipeline::CreationContext<S>,
+    ) -> Result<(), ContextMismatch> {
+        context.check_input(operation, &self.0.borrow().input)
     }
 }
 
-impl<T: Eq + Hash + Clone> FramelessInputInner<T> {
+impl<T: Eq + Hash + Clone, S: BuildHasher> FramelessInputInner<T, S> {
     fn insert(&mut self, value: T) {
         if self.sent.insert(value.clone()) {
             self.input.update(value, 1);
         }
     }
 
+    fn extend(&mut self, values: impl IntoIterator<Item = T>) {
+        let sent = &mut self.sent;
+        self.input.extend(
+            values
+                .into_iter()
+                .filter(|value| sent.insert(value.clone()))
+                .map(|value| (value, 1)),
+        );
+    }
+
     fn insert_all(
         &mut self,
-        output: &mut relation_pipeline::Output<T, impl RelationalOp<T = T>>,
+        output: &mut relation_pipeline::Output<T, impl RelationalOp<T = T>, S>,
     ) -> bool {
         let mut any_sent = false;
-        output.dump_to_map(&mut self.pending_counts);
-        for (value, _count) in self.pending_counts.drain() {
+        output.dump_to_index_map(&mut self.pending_counts);
+        for (value, _count) in self.pending_counts.drain(..) {
             if self.sent.insert(value.clone()) {
                 any_sent = true;
                 self.input.update(value, 1);
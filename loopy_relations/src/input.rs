@@ -1,24 +1,33 @@
-use std::{cell::RefCell, hash::Hash, rc::Rc};
+use std::{
+    cell::RefCell,
+    hash::{BuildHasher, Hash, RandomState},
+    rc::Rc,
+};
 
-use relation_pipeline::RelationalOp;
+use derive_where::derive_where;
+use relation_pipeline::{ContextMismatch, RelationalOp};
 
 use self::inner::InputInner;
 
 mod inner;
 
-#[derive(Clone)]
-pub struct Input<T: Eq + Hash + Clone>(pub FirstOccurrencesInput<T, ()>);
+#[derive_where(Clone)]
+pub struct Input<T: Eq + Hash + Clone, S: BuildHasher = RandomState>(
+    pub FirstOccurrencesInput<T, (), S>,
+);
 
-impl<T: Eq + Hash + Clone> Input<T> {
+impl<T: Eq + Hash + Clone, S: BuildHasher> Input<T, S> {
     pub fn insert(&self, value: T) -> bool {
         self.0.insert(value, ())
     }
 }
 
-#[derive(Clone)]
-pub struct FirstOccurrencesInput<K: Eq + Hash + Clone, V: Ord + Hash + Clone>(
-    Rc<RefCell<InputInner<K, V>>>,
-);
+#[derive_where(Clone)]
+pub struct FirstOccurrencesInput<
+    K: Eq + Hash + Clone,
+    V: Ord + Hash + Clone,
+    S: BuildHasher = RandomState,
+>(Rc<RefCell<InputInner<K, V, S>>>);
 
 pub(crate) trait IsTrackedInput {
     fn push_frame(&mut self);
@@ -26,7 +35,9 @@ pub(crate) trait IsTrackedInput {
     fn pop_frame(&mut self);
 }
 
-impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone> IsTrackedInput for FirstOccurrencesInput<K, V> {
+impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone, S: BuildHasher> IsTrackedInput
+    for FirstOccurrencesInput<K, V, S>
+{
     fn push_frame(&mut self) {
         self.0.borrow_mut().push_frame();
     }
@@ -37,8 +48,11 @@ impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone> IsTrackedInput for FirstOccurr
     }
 }
 
-impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone> FirstOccurrencesInput<K, V> {
-    pub(crate) fn new(inner: relation_pipeline::Input<(K, V)>) -> Self {
+impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone, S: BuildHasher> FirstOccurrencesInput<K, V, S> {
+    pub(crate) fn new(inner: relation_pipeline::Input<(K, V)>) -> Self
+    where
+        S: Default,
+    {
         Self(Rc::new(RefCell::new(InputInner::new(inner))))
     }
 
@@ -48,12 +62,16 @@ impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone> FirstOccurrencesInput<K, V> {
 
     pub(crate) fn insert_all(
         &self,
-        output: &mut relation_pipeline::Output<(K, V), impl RelationalOp<T = (K, V)>>,
+        output: &mut relation_pipeline::Output<(K, V), impl RelationalOp<T = (K, V)>, S>,
     ) -> bool {
         self.0.borrow_mut().insert_all(output)
     }
 
-    pub(crate) fn matches_context(&self, context: &relation_pipeline::CreationContext) -> bool {
-        self.0.borrow().matches_context(context)
+    pub(crate) fn check_context(
+        &self,
+        operation: &'static str,
+        context: &relation_pipeline::CreationContext<S>,
+    ) -> Result<(), ContextMismatch> {
+        self.0.borrow().check_context(operation, context)
     }
 }
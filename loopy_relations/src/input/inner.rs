@@ -1,35 +1,40 @@
-use std::{
-    collections::{HashMap, HashSet},
-    hash::Hash,
-};
+use std::hash::{BuildHasher, Hash};
 
-use l2_heaps::L2Heaps;
-use relation_pipeline::RelationalOp;
+use indexmap::{IndexMap, IndexSet};
+use l2_heaps::{L2Heaps, Max};
+use relation_pipeline::{ContextMismatch, RelationalOp};
 
 use self::inner::InputInnerInner;
 
 #[allow(clippy::module_inception)]
 mod inner;
 
-pub(super) struct InputInner<K: Eq + Hash + Clone, V: Ord + Hash + Clone> {
-    inner: InputInnerInner<K, V>,
-    counts: L2Heaps<K, V, i64>,
-    pending_counts: HashMap<(K, V), i64>,
-    unvisited_keys: HashSet<K>,
+pub(super) struct InputInner<K: Eq + Hash + Clone, V: Ord + Hash + Clone, S: BuildHasher> {
+    inner: InputInnerInner<K, V, S>,
+    counts: L2Heaps<K, V, i64, Max, 2, S>,
+    pending_counts: IndexMap<(K, V), i64, S>,
+    unvisited_keys: IndexSet<K, S>,
 }
 
-impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone> InputInner<K, V> {
-    pub(super) fn new(inner: relation_pipeline::Input<(K, V)>) -> InputInner<K, V> {
+impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone, S: BuildHasher> InputInner<K, V, S> {
+    pub(super) fn new(inner: relation_pipeline::Input<(K, V)>) -> InputInner<K, V, S>
+    where
+        S: Default,
+    {
         InputInner {
             inner: InputInnerInner::new(inner),
-            counts: L2Heaps::new(),
-            pending_counts: HashMap::new(),
-            unvisited_keys: HashSet::new(),
+            counts: L2Heaps::default(),
+            pending_counts: IndexMap::default(),
+            unvisited_keys: IndexSet::default(),
         }
     }
 
-    pub(super) fn matches_context(&self, context: &relation_pipeline::CreationContext) -> bool {
-        self.inner.matches_context(context)
+    pub(super) fn check_context(
+        &self,
+        operation: &'static str,
+        context: &relation_pipeline::CreationContext<S>,
+    ) -> Result<(), ContextMismatch> {
+        self.inner.check_context(operation, context)
     }
 
     pub(super) fn push_frame(&mut self) {
@@ -48,16 +53,16 @@ impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone> InputInner<K, V> {
 
     pub(super) fn insert_all(
         &mut self,
-        output: &mut relation_pipeline::Output<(K, V), impl RelationalOp<T = (K, V)>>,
+        output: &mut relation_pipeline::Output<(K, V), impl RelationalOp<T = (K, V)>, S>,
     ) -> bool {
-        output.dump_to_map(&mut self.pending_counts);
-        for ((k, v), count) in self.pending_counts.drain() {
+        output.dump_to_index_map(&mut self.pending_counts);
+        for ((k, v), count) in self.pending_counts.drain(..) {
             if add_to_counts(&mut self.counts, k.clone(), v, count) {
                 self.unvisited_keys.insert(k);
             }
         }
         let mut any_inserted = false;
-        for key in self.unvisited_keys.drain() {
+        for key in self.unvisited_keys.drain(..) {
             let (max_val, _) = self.counts.get_max(&key).unwrap();
             any_inserted |= self.inner.update(key, max_val.clone());
         }
@@ -65,8 +70,8 @@ impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone> InputInner<K, V> {
     }
 }
 
-fn add_to_counts<K: Clone + Eq + Hash, V: Clone + Ord + Hash>(
-    counts: &mut L2Heaps<K, V, i64>,
+fn add_to_counts<K: Clone + Eq + Hash, V: Clone + Ord + Hash, S: BuildHasher>(
+    counts: &mut L2Heaps<K, V, i64, Max, 2, S>,
     key: K,
     value: V,
     add_count: i64,
@@ -1,26 +1,28 @@
 use std::{
     collections::{HashMap, hash_map},
-    hash::Hash,
+    hash::{BuildHasher, Hash},
 };
 
 use l2_map::L2Map;
+use relation_pipeline::ContextMismatch;
 
-pub(super) struct InputInnerInner<K: Eq + Hash + Clone, V: Ord + Hash + Clone> {
+pub(super) struct InputInnerInner<K: Eq + Hash + Clone, V: Ord + Hash + Clone, S: BuildHasher> {
     inner: relation_pipeline::Input<(K, V)>,
-    sent: HashMap<K, V>,
-    phases: L2Map<usize, K, V>,
+    sent: HashMap<K, V, S>,
+    phases: L2Map<usize, K, V, 2, S>,
     next_phase: usize,
-    popped_keys: Vec<K>,
 }
 
-impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone> InputInnerInner<K, V> {
-    pub(super) fn new(inner: relation_pipeline::Input<(K, V)>) -> Self {
+impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone, S: BuildHasher> InputInnerInner<K, V, S> {
+    pub(super) fn new(inner: relation_pipeline::Input<(K, V)>) -> Self
+    where
+        S: Default,
+    {
         Self {
             inner,
-            sent: HashMap::new(),
-            phases: L2Map::new(),
+            sent: HashMap::default(),
+            phases: L2Map::default(),
             next_phase: 0,
-            popped_keys: Vec::new(),
         }
     }
 
@@ -45,18 +47,18 @@ impl<K: Eq + Hash + Clone, V: Ord + Hash + Clone> InputInnerInner<K, V> {
     pub(super) fn pop_frame(&mut self) {
         assert!(self.next_phase > 0, "no frame to pop");
         let current_phase = self.next_phase - 1;
-        for (key, value) in self.phases.get_iter(&current_phase) {
-            self.sent.remove(key);
-            self.inner.update((key.clone(), value.clone()), -1);
-            self.popped_keys.push(key.clone());
-        }
-        for key in self.popped_keys.drain(..) {
-            self.phases.remove(&current_phase, &key);
+        for (key, value) in self.phases.remove_all(&current_phase) {
+            self.sent.remove(&key);
+            self.inner.update((key, value), -1);
         }
         self.next_phase = current_phase;
     }
 
-    pub(super) fn matches_context(&self, context: &relation_pipeline::CreationContext) -> bool {
-        context.matches_input(&self.inner)
+    pub(super) fn check_context(
+        &self,
+        operation: &'static str,
+        context: &relation_pipeline::CreationContext<S>,
+    ) -> Result<(), ContextMismatch> {
+        context.check_input(operation, &self.inner)
     }
 }
@@ -3,6 +3,7 @@ pub use self::feeder::InterruptId;
 pub use self::frameless_input::FramelessInput;
 pub use self::input::{FirstOccurrencesInput, Input};
 pub use self::output::Output;
+pub use relation_pipeline::FxBuildHasher;
 
 mod context;
 mod feeder;
@@ -1,27 +1,31 @@
-use std::{collections::HashMap, hash::Hash};
+use std::hash::{BuildHasher, Hash, RandomState};
 
+use indexmap::IndexMap;
 use relation_pipeline::{RelationalOp, ops::Dynamic};
 
-pub struct Output<T, Op: RelationalOp<T = T> = Dynamic<'static, T>> {
-    inner: relation_pipeline::Output<T, Op>,
-    values: HashMap<T, i64>,
+pub struct Output<T, Op: RelationalOp<T = T> = Dynamic<'static, T>, S = RandomState> {
+    inner: relation_pipeline::Output<T, Op, S>,
+    values: IndexMap<T, i64, S>,
 }
 
-impl<T: Eq + Hash + Clone, Op: RelationalOp<T = T>> Output<T, Op> {
-    pub fn new(inner: relation_pipeline::Output<T, Op>) -> Self {
+impl<T: Eq + Hash + Clone, Op: RelationalOp<T = T>, S: BuildHasher> Output<T, Op, S> {
+    pub fn new(inner: relation_pipeline::Output<T, Op, S>) -> Self
+    where
+        S: Default,
+    {
         Self {
             inner,
-            values: HashMap::new(),
+            values: IndexMap::default(),
         }
     }
 
     pub fn iter(&mut self) -> impl ExactSizeIterator<Item = &T> {
-        self.inner.dump_to_map(&mut self.values);
+        self.inner.dump_to_index_map(&mut self.values);
         self.values.keys()
     }
 
     pub fn is_empty(&mut self) -> bool {
-        self.inner.dump_to_map(&mut self.values);
+        self.inner.dump_to_index_map(&mut self.values);
         self.values.is_empty()
     }
 }
@@ -0,0 +1,140 @@
+use std::{
+    collections::{HashMap, hash_map},
+    hash::Hash,
+};
+
+pub type Collection<T> = HashMap<T, i64>;
+
+pub fn add<T: Eq + Hash>(collection: &mut Collection<T>, x: T, count: i64) {
+    if count == 0 {
+        return;
+    }
+    match collection.entry(x) {
+        hash_map::Entry::Vacant(e) => {
+            e.insert(count);
+        }
+        hash_map::Entry::Occupied(mut e) => {
+            let current = e.get_mut();
+            *current += count;
+            if *current == 0 {
+                e.remove();
+            }
+        }
+    }
+}
+
+pub fn from_iter<T: Eq + Hash>(values: impl IntoIterator<Item = (T, i64)>) -> Collection<T> {
+    let mut result = Collection::new();
+    for (x, count) in values {
+        add(&mut result, x, count);
+    }
+    result
+}
+
+pub fn flat_map<T, U: Eq + Hash, R: IntoIterator<Item = U>>(
+    collection: &Collection<T>,
+    mut f: impl FnMut(&T) -> R,
+) -> Collection<U> {
+    from_iter(
+        collection
+            .iter()
+            .flat_map(|(x, &count)| f(x).into_iter().map(move |y| (y, count))),
+    )
+}
+
+pub fn filter<T: Clone + Eq + Hash>(
+    collection: &Collection<T>,
+    mut f: impl FnMut(&T) -> bool,
+) -> Collection<T> {
+    flat_map(collection, |x| f(x).then(|| x.clone()))
+}
+
+pub fn map<T, U: Eq + Hash>(
+    collection: &Collection<T>,
+    mut f: impl FnMut(&T) -> U,
+) -> Collection<U> {
+    flat_map(collection, |x| [f(x)])
+}
+
+pub fn concat<T: Clone + Eq + Hash>(left: &Collection<T>, right: &Collection<T>) -> Collection<T> {
+    from_iter(
+        left.iter()
+            .chain(right)
+            .map(|(x, &count)| (x.clone(), count)),
+    )
+}
+
+pub fn distinct<T: Clone + Eq + Hash>(collection: &Collection<T>) -> Collection<T> {
+    map(collection, T::clone)
+        .into_keys()
+        .map(|x| (x, 1))
+        .collect()
+}
+
+pub fn counts<T: Clone + Eq + Hash>(collection: &Collection<T>) -> Collection<(T, i64)> {
+    collection
+        .iter()
+        .map(|(x, &count)| ((x.clone(), count), 1))
+        .collect()
+}
+
+pub fn join<K, V1, V2>(
+    left: &Collection<(K, V1)>,
+    right: &Collection<(K, V2)>,
+) -> Collection<(K, (V1, V2))>
+where
+    K: Clone + Eq + Hash,
+    V1: Clone + Eq + Hash,
+    V2: Clone + Eq + Hash,
+{
+    let mut result = Collection::new();
+    for ((k1, v1), &n1) in left {
+        for ((k2, v2), &n2) in right {
+            if k1 == k2 {
+                add(&mut result, (k1.clone(), (v1.clone(), v2.clone())), n1 * n2);
+            }
+        }
+    }
+    result
+}
+
+pub fn antijoin<K, V>(left: &Collection<(K, V)>, right: &Collection<K>) -> Collection<(K, V)>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+{
+    left.iter()
+        .filter(|((k, _), _)| !right.contains_key(k))
+        .map(|(kv, &count)| (kv.clone(), count))
+        .collect()
+}
+
+pub fn split<K, V>(collection: &Collection<(K, V)>) -> (Collection<K>, Collection<V>)
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+{
+    (
+        map(collection, |(k, _)| k.clone()),
+        map(collection, |(_, v)| v.clone()),
+    )
+}
+
+pub fn top_ns<K, V>(collection: &Collection<(K, V)>, n: usize) -> Collection<(K, Vec<V>)>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Ord + Hash,
+{
+    let mut groups: HashMap<K, Vec<V>> = HashMap::new();
+    for (k, v) in collection.keys() {
+        groups.entry(k.clone()).or_default().push(v.clone());
+    }
+    groups
+        .into_iter()
+        .filter_map(|(k, mut vs)| {
+            vs.sort_by(|a, b| b.cmp(a));
+            vs.truncate(n);
+            (!vs.is_empty()).then_some(((k, vs), 1))
+        })
+        .collect()
+}
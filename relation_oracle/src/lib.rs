@@ -0,0 +1,186 @@
+use std::{fmt::Debug, hash::Hash};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use relation_pipeline::{CreationContext, InputRelation, Relation, RelationalOp};
+
+use self::batch::Collection;
+
+pub mod batch;
+
+pub type Updates<T> = Vec<Vec<(T, i64)>>;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    pub seed: u64,
+    pub runs: usize,
+    pub commits: usize,
+    pub max_batch: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            seed: 31416,
+            runs: 100,
+            commits: 8,
+            max_batch: 6,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Mismatch<U> {
+    pub commit: usize,
+    pub expected: Collection<U>,
+    pub actual: Collection<U>,
+}
+
+#[track_caller]
+pub fn check<T, U, Op>(
+    gen_value: impl FnMut(&mut StdRng) -> T,
+    pipeline: impl Fn(&CreationContext, InputRelation<T>) -> Relation<U, Op>,
+    oracle: impl Fn(&Collection<T>) -> Collection<U>,
+) where
+    T: Clone + Eq + Hash + Debug,
+    U: Eq + Hash + Debug,
+    Op: RelationalOp<T = U>,
+{
+    check_with(Config::default(), gen_value, pipeline, oracle)
+}
+
+#[track_caller]
+pub fn check_with<T, U, Op>(
+    config: Config,
+    mut gen_value: impl FnMut(&mut StdRng) -> T,
+    pipeline: impl Fn(&CreationContext, InputRelation<T>) -> Relation<U, Op>,
+    oracle: impl Fn(&Collection<T>) -> Collection<U>,
+) where
+    T: Clone + Eq + Hash + Debug,
+    U: Eq + Hash + Debug,
+    Op: RelationalOp<T = U>,
+{
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    for _ in 0..config.runs {
+        let updates = random_updates(&mut rng, &mut gen_value, config);
+        if run(&updates, &pipeline, &oracle).is_err() {
+            let updates = shrink(updates, &pipeline, &oracle);
+            let mismatch = run(&updates, &pipeline, &oracle).unwrap_err();
+            panic!("incremental output diverged from oracle\nupdates: {updates:?}\n{mismatch:#?}");
+        }
+    }
+}
+
+pub fn run<T, U, Op>(
+    updates: &Updates<T>,
+    pipeline: impl Fn(&CreationContext, InputRelation<T>) -> Relation<U, Op>,
+    oracle: impl Fn(&Collection<T>) -> Collection<U>,
+) -> Result<(), Mismatch<U>>
+where
+    T: Clone + Eq + Hash,
+    U: Eq + Hash,
+    Op: RelationalOp<T = U>,
+{
+    let context = CreationContext::new();
+    let (input, relation) = context.new_input();
+    let mut output = context.output(pipeline(&context, relation));
+    let mut context = context.begin();
+    let mut inputs = Collection::new();
+    let mut actual = Collection::new();
+    for (commit, batch) in updates.iter().enumerate() {
+        for (x, count) in batch {
+            input.update(x.clone(), *count);
+            batch::add(&mut inputs, x.clone(), *count);
+        }
+        context.commit();
+        output.dump_to_map(&mut actual);
+        let expected = oracle(&inputs);
+        if actual != expected {
+            return Err(Mismatch {
+                commit,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn random_updates<T: Clone + Eq + Hash>(
+    rng: &mut StdRng,
+    mut gen_value: impl FnMut(&mut StdRng) -> T,
+    config: Config,
+) -> Updates<T> {
+    let mut inputs: Collection<T> = Collection::new();
+    let mut updates = Vec::new();
+    for _ in 0..config.commits {
+        let mut batch = Vec::new();
+        for _ in 0..rng.random_range(0..=config.max_batch) {
+            let update = if !inputs.is_empty() && rng.random_bool(0.3) {
+                let i = rng.random_range(0..inputs.len());
+                let (x, &count) = inputs.iter().nth(i).unwrap();
+                (x.clone(), -count)
+            } else {
+                let count = [-2, -1, 1, 1, 1, 2, 3][rng.random_range(0..7)];
+                (gen_value(rng), count)
+            };
+            batch::add(&mut inputs, update.0.clone(), update.1);
+            batch.push(update);
+        }
+        updates.push(batch);
+    }
+    updates
+}
+
+pub fn shrink<T, U, Op>(
+    mut updates: Updates<T>,
+    pipeline: impl Fn(&CreationContext, InputRelation<T>) -> Relation<U, Op>,
+    oracle: impl Fn(&Collection<T>) -> Collection<U>,
+) -> Updates<T>
+where
+    T: Clone + Eq + Hash,
+    U: Eq + Hash,
+    Op: RelationalOp<T = U>,
+{
+    let fails = |updates: &Updates<T>| run(updates, &pipeline, &oracle).is_err();
+    loop {
+        let mut changed = false;
+        let mut i = updates.len();
+        while i > 0 {
+            i -= 1;
+            let mut candidate = updates.clone();
+            candidate.remove(i);
+            if fails(&candidate) {
+                updates = candidate;
+                changed = true;
+            }
+        }
+        for i in 0..updates.len() {
+            let mut j = updates[i].len();
+            while j > 0 {
+                j -= 1;
+                let mut candidate = updates.clone();
+                candidate[i].remove(j);
+                if fails(&candidate) {
+                    updates = candidate;
+                    changed = true;
+                }
+            }
+        }
+        for i in 0..updates.len() {
+            for j in 0..updates[i].len() {
+                let count = updates[i][j].1;
+                if count.abs() > 1 {
+                    let mut candidate = updates.clone();
+                    candidate[i][j].1 = count.signum();
+                    if fails(&candidate) {
+                        updates = candidate;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        if !changed {
+            return updates;
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use either::Either::{self, Left, Right};
+use rand::{Rng, rngs::StdRng};
+use relation_oracle::batch::{self, Collection};
+use relation_pipeline::SpillConfig;
+
+fn gen_pair(rng: &mut StdRng) -> (u8, u8) {
+    (rng.random_range(0..3), rng.random_range(0..4))
+}
+
+fn gen_either<L, R>(
+    rng: &mut StdRng,
+    mut gen_left: impl FnMut(&mut StdRng) -> L,
+    mut gen_right: impl FnMut(&mut StdRng) -> R,
+) -> Either<L, R> {
+    if rng.random_bool(0.5) {
+        Left(gen_left(rng))
+    } else {
+        Right(gen_right(rng))
+    }
+}
+
+fn partition<L: Clone + Eq + std::hash::Hash, R: Clone + Eq + std::hash::Hash>(
+    inputs: &Collection<Either<L, R>>,
+) -> (Collection<L>, Collection<R>) {
+    (
+        batch::flat_map(inputs, |x| x.clone().left()),
+        batch::flat_map(inputs, |x| x.clone().right()),
+    )
+}
+
+#[test]
+fn test_join() {
+    relation_oracle::check(
+        |rng| gen_either(rng, gen_pair, gen_pair),
+        |_, relation| {
+            let (left, right) = relation.partition();
+            left.join(right)
+        },
+        |inputs| {
+            let (left, right) = partition(inputs);
+            batch::join(&left, &right)
+        },
+    );
+}
+
+#[test]
+fn test_join_spilled() {
+    let config = SpillConfig::new(std::env::temp_dir(), 2);
+    relation_oracle::check(
+        |rng| gen_either(rng, gen_pair, gen_pair),
+        |_, relation| {
+            let (left, right) = relation.partition();
+            left.join_spilled(right, &config)
+        },
+        |inputs| {
+            let (left, right) = partition(inputs);
+            batch::join(&left, &right)
+        },
+    );
+}
+
+#[test]
+fn test_range_join() {
+    relation_oracle::check(
+        |rng| gen_either(rng, gen_pair, gen_pair),
+        |_, relation| {
+            let (left, right) = relation.partition();
+            left.range_join(right, |&v| v.saturating_sub(1)..=v + 1)
+        },
+        |inputs| {
+            let (left, right) = partition(inputs);
+            batch::filter(&batch::join(&left, &right), |&(_, (v1, v2))| {
+                v1.abs_diff(v2) <= 1
+            })
+        },
+    );
+}
+
+#[test]
+fn test_antijoin() {
+    relation_oracle::check(
+        |rng| gen_either(rng, gen_pair, |rng| rng.random_range(0..3)),
+        |_, relation| {
+            let (left, right) = relation.partition();
+            left.antijoin(right)
+        },
+        |inputs| {
+            let (left, right) = partition(inputs);
+            batch::antijoin(&left, &right)
+        },
+    );
+}
+
+#[test]
+fn test_antijoin_spilled() {
+    let config = SpillConfig::new(std::env::temp_dir(), 2);
+    relation_oracle::check(
+        |rng| gen_either(rng, gen_pair, |rng| rng.random_range(0..3)),
+        |_, relation| {
+            let (left, right) = relation.partition();
+            left.antijoin_spilled(right, &config)
+        },
+        |inputs| {
+            let (left, right) = partition(inputs);
+            batch::antijoin(&left, &right)
+        },
+    );
+}
+
+#[test]
+fn test_top_ns() {
+    relation_oracle::check(
+        gen_pair,
+        |_, relation| relation.top_ns::<2>().map(|(k, vs)| (k, vs.to_vec())),
+        |inputs| batch::top_ns(inputs, 2),
+    );
+}
+
+#[test]
+fn test_top_n() {
+    relation_oracle::check(
+        gen_pair,
+        |_, relation| relation.top_n(2),
+        |inputs| batch::top_ns(inputs, 2),
+    );
+}
+
+#[test]
+fn test_bottom_n_ranked() {
+    relation_oracle::check(
+        gen_pair,
+        |_, relation| relation.bottom_n_ranked(2),
+        |inputs| {
+            let bottoms = batch::map(inputs, |&(k, v)| (k, std::cmp::Reverse(v)));
+            batch::flat_map(&batch::top_ns(&bottoms, 2), |(k, vs)| {
+                let k = *k;
+                vs.iter()
+                    .enumerate()
+                    .map(|(i, &std::cmp::Reverse(v))| (k, i, v))
+                    .collect::<Vec<_>>()
+            })
+        },
+    );
+}
+
+#[test]
+fn test_top_n_by() {
+    relation_oracle::check(
+        |rng| gen_either(rng, gen_pair, |rng| rng.random_range(0..4usize)),
+        |_, relation| {
+            let (values, limit) = relation.partition();
+            values.top_n_by(limit)
+        },
+        |inputs| {
+            let (values, limit) = partition(inputs);
+            batch::top_ns(&values, limit.keys().copied().max().unwrap_or(0))
+        },
+    );
+}
+
+#[test]
+fn test_rank_by_key() {
+    relation_oracle::check(
+        gen_pair,
+        |_, relation| relation.rank_by_key(),
+        |inputs| {
+            batch::flat_map(&batch::top_ns(inputs, usize::MAX), |(k, vs)| {
+                let k = *k;
+                vs.iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(i, &v)| (k, i, v))
+                    .collect::<Vec<_>>()
+            })
+        },
+    );
+}
+
+#[test]
+fn test_counts() {
+    relation_oracle::check(
+        |rng| rng.random_range(0..5u8),
+        |_, relation| relation.counts(),
+        batch::counts,
+    );
+}
+
+#[test]
+fn test_distinct() {
+    relation_oracle::check(
+        |rng| rng.random_range(0..5u8),
+        |_, relation| relation.distinct(),
+        batch::distinct,
+    );
+}
+
+#[test]
+fn test_split() {
+    relation_oracle::check(
+        gen_pair,
+        |_, relation| {
+            let (left, right) = relation.split();
+            left.map(Left).concat(right.map(Right))
+        },
+        |inputs| {
+            let (left, right) = batch::split(inputs);
+            batch::concat(
+                &batch::map(&left, |&k| Left(k)),
+                &batch::map(&right, |&v| Right(v)),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_save() {
+    relation_oracle::check(
+        gen_pair,
+        |_, relation| {
+            let saved = relation.save();
+            saved.get_().concat(saved.get().swaps().swaps())
+        },
+        |inputs| batch::concat(inputs, inputs),
+    );
+}
+
+#[test]
+#[should_panic(expected = "incremental output diverged from oracle")]
+fn test_detects_mismatch() {
+    relation_oracle::check(
+        |rng| rng.random_range(0..5u8),
+        |_, relation| relation.distinct(),
+        |inputs| batch::map(inputs, |&x| x),
+    );
+}
@@ -1,63 +1,142 @@
-use std::{cell::Cell, rc::Rc};
+use std::{cell::Cell, hash::RandomState, marker::PhantomData, rc::Rc};
 
-use crate::{Input, InputRelation, Output, Relation, RelationalOp, ops::InputOp};
+use derive_where::derive_where;
+
+use crate::{
+    ContextId, ContextMismatch, IndexedOutput, Input, InputRelation, Operand, Output, Relation,
+    RelationalOp, ops::InputOp,
+};
 
 #[derive(Default)]
-pub struct CreationContext {
-    commit_id: Rc<Cell<u64>>,
+pub(crate) struct ContextState {
+    pub(crate) commit_id: Cell<u64>,
+    pub(crate) deterministic: bool,
+}
+
+#[derive_where(Default)]
+pub struct CreationContext<S = RandomState> {
+    state: Rc<ContextState>,
+    hasher: PhantomData<fn() -> S>,
 }
 
 impl CreationContext {
     pub fn new() -> Self {
         Self::default()
     }
-    pub fn new_input<T>(&self) -> (Input<T>, InputRelation<T>) {
+}
+
+impl<S> CreationContext<S> {
+    #[track_caller]
+    pub fn deterministic(mut self) -> Self {
+        Rc::get_mut(&mut self.state)
+            .expect("deterministic mode must be enabled before creating relations")
+            .deterministic = true;
+        self
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.state.deterministic
+    }
+
+    pub fn new_input<T>(&self) -> (Input<T>, InputRelation<T, S>) {
+        self.new_input_with(Vec::new())
+    }
+
+    pub fn new_input_with<T>(
+        &self,
+        initial: impl IntoIterator<Item = (T, i64)>,
+    ) -> (Input<T>, InputRelation<T, S>) {
         let (sender, receiver) = swap_channel::new();
         (
-            Input::new(sender, Rc::clone(&self.commit_id)),
-            Relation::new(InputOp::new(receiver), self.commit_id.clone()),
+            Input::new(sender, Rc::clone(&self.state)),
+            Relation::new(
+                InputOp::new(Vec::from_iter(initial), receiver),
+                self.state.clone(),
+            ),
         )
     }
 
+    pub fn id(&self) -> ContextId {
+        ContextId::of(&self.state)
+    }
+
+    #[track_caller]
+    pub fn output<T, Op: RelationalOp<T = T>>(
+        &self,
+        relation: Relation<T, Op, S>,
+    ) -> Output<T, Op, S> {
+        self.try_output(relation).unwrap()
+    }
+
+    pub fn try_output<T, Op: RelationalOp<T = T>>(
+        &self,
+        relation: Relation<T, Op, S>,
+    ) -> Result<Output<T, Op, S>, ContextMismatch> {
+        self.check_relation("output", &relation)?;
+        Ok(Output(relation))
+    }
+
     #[track_caller]
-    pub fn output<T, Op: RelationalOp<T = T>>(&self, relation: Relation<T, Op>) -> Output<T, Op> {
-        assert!(self.matches_relation(&relation));
-        Output(relation)
+    pub fn indexed_output<K: Ord, V: Ord, Op: RelationalOp<T = (K, V)>>(
+        &self,
+        relation: Relation<(K, V), Op, S>,
+    ) -> IndexedOutput<K, V, Op, S> {
+        IndexedOutput::new(self.output(relation))
     }
 
     pub fn begin(self) -> ExecutionContext {
-        ExecutionContext {
-            commit_id: self.commit_id,
-        }
+        ExecutionContext { state: self.state }
     }
 
-    pub fn constant<T>(&self, values: impl IntoIterator<Item = (T, i64)>) -> InputRelation<T> {
-        let (input, relation) = self.new_input();
-        for (x, count) in values {
-            input.update(x, count);
-        }
+    pub fn constant<T>(&self, values: impl IntoIterator<Item = (T, i64)>) -> InputRelation<T, S> {
+        let (_input, relation) = self.new_input_with(values);
         relation
     }
 
-    pub fn matches_relation<T, Op: RelationalOp<T = T>>(&self, relation: &Relation<T, Op>) -> bool {
-        Rc::ptr_eq(&self.commit_id, &relation.current_commit_id)
+    pub fn matches_relation<T, Op: RelationalOp<T = T>>(
+        &self,
+        relation: &Relation<T, Op, S>,
+    ) -> bool {
+        Rc::ptr_eq(&self.state, &relation.context)
     }
 
     pub fn matches_input<T>(&self, input: &Input<T>) -> bool {
-        Rc::ptr_eq(&self.commit_id, input.commit_id())
+        Rc::ptr_eq(&self.state, input.context())
     }
 
-    pub fn matches_output<T, Op: RelationalOp<T = T>>(&self, output: &Output<T, Op>) -> bool {
+    pub fn matches_output<T, Op: RelationalOp<T = T>>(&self, output: &Output<T, Op, S>) -> bool {
         self.matches_relation(&output.0)
     }
+
+    pub fn check_relation<T, Op: RelationalOp<T = T>>(
+        &self,
+        operation: &'static str,
+        relation: &Relation<T, Op, S>,
+    ) -> Result<(), ContextMismatch> {
+        ContextMismatch::check(
+            operation,
+            Operand::Relation,
+            self.id(),
+            relation.context_id(),
+        )
+    }
+
+    pub fn check_input<T>(
+        &self,
+        operation: &'static str,
+        input: &Input<T>,
+    ) -> Result<(), ContextMismatch> {
+        ContextMismatch::check(operation, Operand::Input, self.id(), input.context_id())
+    }
 }
 
 pub struct ExecutionContext {
-    commit_id: Rc<Cell<u64>>,
+    state: Rc<ContextState>,
 }
 
 impl ExecutionContext {
     pub fn commit(&mut self) {
-        self.commit_id.set(self.commit_id.get() + 1);
+        let commit_id = &self.state.commit_id;
+        commit_id.set(commit_id.get() + 1);
     }
 }
@@ -0,0 +1,79 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    rc::Rc,
+};
+
+use crate::context::ContextState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContextId(usize);
+
+impl ContextId {
+    pub(crate) fn of(context: &Rc<ContextState>) -> Self {
+        Self(Rc::as_ptr(context) as usize)
+    }
+}
+
+impl Display for ContextId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:x}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Relation,
+    Other,
+    Input,
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Relation => write!(f, "relation"),
+            Self::Other => write!(f, "other relation"),
+            Self::Input => write!(f, "input"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextMismatch {
+    pub operation: &'static str,
+    pub operand: Operand,
+    pub expected: ContextId,
+    pub found: ContextId,
+}
+
+impl ContextMismatch {
+    pub fn check(
+        operation: &'static str,
+        operand: Operand,
+        expected: ContextId,
+        found: ContextId,
+    ) -> Result<(), Self> {
+        if expected == found {
+            Ok(())
+        } else {
+            Err(Self {
+                operation,
+                operand,
+                expected,
+                found,
+            })
+        }
+    }
+}
+
+impl Display for ContextMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} belongs to context {} but context {} was expected",
+            self.operation, self.operand, self.found, self.expected
+        )
+    }
+}
+
+impl Error for ContextMismatch {}
@@ -0,0 +1,124 @@
+use std::{
+    collections::{BTreeMap, btree_map},
+    hash::RandomState,
+    ops::RangeBounds,
+};
+
+use crate::{Output, RelationalOp, ops::Dynamic};
+
+pub struct IndexedOutput<
+    K,
+    V,
+    Op: RelationalOp<T = (K, V)> = Dynamic<'static, (K, V)>,
+    S = RandomState,
+> {
+    output: Output<(K, V), Op, S>,
+    index: BTreeMap<K, BTreeMap<V, i64>>,
+}
+
+impl<K: Ord, V: Ord, Op: RelationalOp<T = (K, V)>, S> IndexedOutput<K, V, Op, S> {
+    pub(crate) fn new(output: Output<(K, V), Op, S>) -> Self {
+        Self {
+            output,
+            index: BTreeMap::new(),
+        }
+    }
+
+    fn refresh(&mut self) {
+        let index = &mut self.index;
+        self.output.for_each(|(k, v), n| {
+            if n == 0 {
+                return;
+            }
+            match index.entry(k) {
+                btree_map::Entry::Vacant(e) => {
+                    e.insert(BTreeMap::from([(v, n)]));
+                }
+                btree_map::Entry::Occupied(mut e) => {
+                    let values = e.get_mut();
+                    match values.entry(v) {
+                        btree_map::Entry::Vacant(value_e) => {
+                            value_e.insert(n);
+                        }
+                        btree_map::Entry::Occupied(mut value_e) => {
+                            let count = value_e.get_mut();
+                            *count += n;
+                            if *count == 0 {
+                                value_e.remove();
+                                if values.is_empty() {
+                                    e.remove();
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn get<'a>(
+        &'a mut self,
+        key: &K,
+    ) -> impl Iterator<Item = (&'a V, i64)> + use<'a, K, V, Op, S> {
+        self.refresh();
+        self.index
+            .get(key)
+            .into_iter()
+            .flat_map(|values| values.iter().map(|(v, &n)| (v, n)))
+    }
+
+    pub fn first_n<'a>(
+        &'a mut self,
+        key: &K,
+        n: usize,
+    ) -> impl Iterator<Item = (&'a V, i64)> + use<'a, K, V, Op, S> {
+        self.get(key).take(n)
+    }
+
+    pub fn last_n<'a>(
+        &'a mut self,
+        key: &K,
+        n: usize,
+    ) -> impl Iterator<Item = (&'a V, i64)> + use<'a, K, V, Op, S> {
+        self.refresh();
+        self.index
+            .get(key)
+            .into_iter()
+            .flat_map(|values| values.iter().rev().map(|(v, &n)| (v, n)))
+            .take(n)
+    }
+
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self.refresh();
+        self.index.contains_key(key)
+    }
+
+    pub fn contains(&mut self, key: &K, value: &V) -> bool {
+        self.count(key, value) != 0
+    }
+
+    pub fn count(&mut self, key: &K, value: &V) -> i64 {
+        self.refresh();
+        self.index
+            .get(key)
+            .and_then(|values| values.get(value))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn range<R: RangeBounds<K>>(&mut self, range: R) -> impl Iterator<Item = (&K, &V, i64)> {
+        self.refresh();
+        self.index
+            .range(range)
+            .flat_map(|(k, values)| values.iter().map(move |(v, &n)| (k, v, n)))
+    }
+
+    pub fn iter(&mut self) -> impl Iterator<Item = (&K, &V, i64)> {
+        self.range(..)
+    }
+
+    pub fn is_empty(&mut self) -> bool {
+        self.refresh();
+        self.index.is_empty()
+    }
+}
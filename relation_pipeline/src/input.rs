@@ -1,26 +1,52 @@
-use std::{cell::Cell, rc::Rc};
+use std::{hash::Hash, rc::Rc};
 
 use derive_where::derive_where;
+use indexmap::IndexMap;
 use swap_channel::Sender;
 
-use crate::op::CommitId;
+use crate::{ContextId, context::ContextState, op::CommitId};
 
 #[derive_where(Clone)]
 pub struct Input<T> {
     sender: Sender<(T, CommitId, i64)>,
-    commit_id: Rc<Cell<u64>>,
+    context: Rc<ContextState>,
 }
 
 impl<T> Input<T> {
-    pub(crate) fn new(sender: Sender<(T, CommitId, i64)>, commit_id: Rc<Cell<u64>>) -> Self {
-        Self { sender, commit_id }
+    pub(crate) fn new(sender: Sender<(T, CommitId, i64)>, context: Rc<ContextState>) -> Self {
+        Self { sender, context }
     }
 
-    pub(crate) fn commit_id(&self) -> &Rc<Cell<u64>> {
-        &self.commit_id
+    pub(crate) fn context(&self) -> &Rc<ContextState> {
+        &self.context
+    }
+
+    pub fn context_id(&self) -> ContextId {
+        ContextId::of(&self.context)
     }
 
     pub fn update(&self, value: T, count: i64) {
-        self.sender.send((value, self.commit_id.get() + 1, count));
+        self.sender
+            .send((value, self.context.commit_id.get() + 1, count));
+    }
+
+    pub fn extend(&self, values: impl IntoIterator<Item = (T, i64)>) {
+        let commit_id = self.context.commit_id.get() + 1;
+        self.sender.send_all(
+            values
+                .into_iter()
+                .map(|(value, count)| (value, commit_id, count)),
+        );
+    }
+
+    pub fn extend_consolidated(&self, values: impl IntoIterator<Item = (T, i64)>)
+    where
+        T: Eq + Hash,
+    {
+        let mut counts = IndexMap::new();
+        for (value, count) in values {
+            *counts.entry(value).or_insert(0) += count;
+        }
+        self.extend(counts.into_iter().filter(|&(_, count)| count != 0));
     }
 }
@@ -1,13 +1,22 @@
-use std::hash::{Hash, Hasher};
-use std::{cmp::Reverse, collections::hash_map::DefaultHasher, convert::identity, iter, rc::Rc};
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+use std::{convert::identity, iter, ops::RangeBounds, rc::Rc};
 
 use arrayvec::ArrayVec;
 use either::Either;
+use l2_heaps::{Max, Min};
+use l2_map::L2Map;
+use siphasher::sip::SipHasher13;
+use spill_map::SpillMap;
 
 use self::ops::{Consolidate, Dynamic};
 
+pub use rustc_hash::FxBuildHasher;
+pub use spill_map::{Codec, SpillConfig};
+
 pub use self::{
     context::{CreationContext, ExecutionContext},
+    error::{ContextId, ContextMismatch, Operand},
+    indexed_output::IndexedOutput,
     input::Input,
     op::RelationalOp,
     ops::Save,
@@ -16,20 +25,24 @@ pub use self::{
 };
 
 mod context;
+mod error;
+mod indexed_output;
 mod input;
 mod op;
 mod output;
 mod relation;
+#[cfg(feature = "serde")]
+mod snapshot;
 
 pub mod ops;
 
-pub type InputRelation<T> = Relation<T, self::ops::InputOp<T>>;
+pub type InputRelation<T, S = RandomState> = Relation<T, self::ops::InputOp<T>, S>;
 
-impl<T, Op: RelationalOp<T = T>> Relation<T, Op> {
+impl<T, Op: RelationalOp<T = T>, S: BuildHasher + Default> Relation<T, Op, S> {
     pub fn cartesian_product<U: Clone + Eq + Hash>(
         self,
-        other: Relation<U, impl RelationalOp<T = U>>,
-    ) -> Relation<(T, U), impl RelationalOp<T = (T, U)>>
+        other: Relation<U, impl RelationalOp<T = U>, S>,
+    ) -> Relation<(T, U), impl RelationalOp<T = (T, U)>, S>
     where
         T: Clone + Eq + Hash,
     {
@@ -37,83 +50,84 @@ impl<T, Op: RelationalOp<T = T>> Relation<T, Op> {
             .join(other.map_h(|u| ((), u)))
             .map_h(|((), (t, u))| (t, u))
     }
+    #[track_caller]
     pub fn concat(
         self,
-        other: Relation<T, impl RelationalOp<T = T>>,
-    ) -> Relation<T, impl RelationalOp<T = T>> {
-        assert!(Rc::ptr_eq(
-            &self.current_commit_id,
-            &other.current_commit_id
-        ));
-        Relation::new(
+        other: Relation<T, impl RelationalOp<T = T>, S>,
+    ) -> Relation<T, impl RelationalOp<T = T>, S> {
+        self.try_concat(other).unwrap()
+    }
+    pub fn try_concat(
+        self,
+        other: Relation<T, impl RelationalOp<T = T>, S>,
+    ) -> Result<Relation<T, impl RelationalOp<T = T>, S>, ContextMismatch> {
+        self.check_same_context("concat", &other)?;
+        Ok(Relation::new(
             ops::Concat::new(self.relation, other.relation),
-            self.current_commit_id,
-        )
+            self.context,
+        ))
     }
-    pub fn consolidate(self) -> Relation<T, Consolidate<T, Op::Unconsolidated>>
+    pub fn consolidate(self) -> Relation<T, Consolidate<T, Op::Unconsolidated, S>, S>
     where
         T: Eq + Hash,
     {
         Relation::new(
-            ops::Consolidate::new(self.relation.op.unconsolidate()),
-            self.current_commit_id,
+            ops::Consolidate::new(self.relation.op.unconsolidate(), self.context.deterministic),
+            self.context,
         )
     }
-    pub fn counts(self) -> Relation<(T, i64), impl RelationalOp<T = (T, i64)>>
+    pub fn counts(self) -> Relation<(T, i64), impl RelationalOp<T = (T, i64)>, S>
     where
         T: Clone + Eq + Hash,
     {
-        Relation::new(ops::Counts::new(self.relation), self.current_commit_id)
+        Relation::new(ops::Counts::<_, _, S>::new(self.relation), self.context)
     }
-    pub fn distinct(self) -> Relation<T, impl RelationalOp<T = T>>
+    pub fn distinct(self) -> Relation<T, impl RelationalOp<T = T>, S>
     where
         T: Clone + Eq + Hash,
     {
-        Relation::new(ops::Distinct::new(self.relation), self.current_commit_id)
+        Relation::new(ops::Distinct::<_, _, S>::new(self.relation), self.context)
     }
-    pub fn dynamic<'a>(self) -> Relation<T, Dynamic<'a, T>>
+    pub fn dynamic<'a>(self) -> Relation<T, Dynamic<'a, T>, S>
     where
         Op: 'a,
     {
-        Relation::new(Dynamic::new(self.relation.op), self.current_commit_id)
+        Relation::new(Dynamic::new(self.relation.op), self.context)
     }
-    pub fn filter(self, mut f: impl FnMut(&T) -> bool) -> Relation<T, impl RelationalOp<T = T>> {
+    pub fn filter(self, mut f: impl FnMut(&T) -> bool) -> Relation<T, impl RelationalOp<T = T>, S> {
         self.flat_map(move |t| f(&t).then_some(t))
     }
     pub fn flat_map_h<U, R: IntoIterator<Item = U>>(
         self,
         f: impl FnMut(T) -> R,
-    ) -> Relation<U, impl RelationalOp<T = U>> {
-        Relation::new(
-            ops::FlatMap::new(self.relation.op, f),
-            self.current_commit_id,
-        )
+    ) -> Relation<U, impl RelationalOp<T = U>, S> {
+        Relation::new(ops::FlatMap::new(self.relation.op, f), self.context)
     }
     pub fn flat_map<U, R: IntoIterator<Item = U>>(
         self,
         f: impl FnMut(T) -> R,
-    ) -> Relation<U, impl RelationalOp<T = U>> {
-        Relation::new(ops::FlatMap::new(self.relation, f), self.current_commit_id)
+    ) -> Relation<U, impl RelationalOp<T = U>, S> {
+        Relation::new(ops::FlatMap::new(self.relation, f), self.context)
     }
-    pub fn flatten_h<U>(self) -> Relation<U, impl RelationalOp<T = U>>
+    pub fn flatten_h<U>(self) -> Relation<U, impl RelationalOp<T = U>, S>
     where
         T: IntoIterator<Item = U>,
     {
         self.flat_map_h(identity)
     }
-    pub fn flatten<U>(self) -> Relation<U, impl RelationalOp<T = U>>
+    pub fn flatten<U>(self) -> Relation<U, impl RelationalOp<T = U>, S>
     where
         T: IntoIterator<Item = U>,
     {
         self.flat_map(identity)
     }
-    pub fn global_max(self) -> Relation<T, impl RelationalOp<T = T>>
+    pub fn global_max(self) -> Relation<T, impl RelationalOp<T = T>, S>
     where
         T: Clone + Ord + Hash,
     {
         self.map_h(|t| ((), t)).maxes().map_h(|((), t)| t)
     }
-    pub fn global_min(self) -> Relation<T, impl RelationalOp<T = T>>
+    pub fn global_min(self) -> Relation<T, impl RelationalOp<T = T>, S>
     where
         T: Clone + Ord + Hash,
     {
@@ -121,8 +135,8 @@ impl<T, Op: RelationalOp<T = T>> Relation<T, Op> {
     }
     pub fn intersection(
         self,
-        other: Relation<T, impl RelationalOp<T = T>>,
-    ) -> Relation<T, impl RelationalOp<T = T>>
+        other: Relation<T, impl RelationalOp<T = T>, S>,
+    ) -> Relation<T, impl RelationalOp<T = T>, S>
     where
         T: Clone + Eq + Hash,
     {
@@ -130,28 +144,28 @@ impl<T, Op: RelationalOp<T = T>> Relation<T, Op> {
             .join(other.map_h(|t| (t, ())))
             .map_h(|(t, ((), ()))| t)
     }
-    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Relation<U, impl RelationalOp<T = U>> {
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Relation<U, impl RelationalOp<T = U>, S> {
         self.flat_map(move |t| iter::once(f(t)))
     }
-    pub fn map_h<U>(self, mut f: impl FnMut(T) -> U) -> Relation<U, impl RelationalOp<T = U>> {
+    pub fn map_h<U>(self, mut f: impl FnMut(T) -> U) -> Relation<U, impl RelationalOp<T = U>, S> {
         self.flat_map_h(move |t| iter::once(f(t)))
     }
     pub fn set_minus(
         self,
-        other: Relation<T, impl RelationalOp<T = T>>,
-    ) -> Relation<T, impl RelationalOp<T = T>>
+        other: Relation<T, impl RelationalOp<T = T>, S>,
+    ) -> Relation<T, impl RelationalOp<T = T>, S>
     where
         T: Clone + Eq + Hash,
     {
         self.map_h(|t| (t, ())).antijoin(other).map_h(|(t, ())| t)
     }
-    pub fn save(self) -> Save<T, Op>
+    pub fn save(self) -> Save<T, Op, S>
     where
         T: Clone,
     {
-        Save::new(self.relation.op, self.current_commit_id)
+        Save::new(self.relation.op, self.context)
     }
-    pub fn collect(self) -> Save<T>
+    pub fn collect(self) -> Save<T, Dynamic<'static, T>, S>
     where
         T: Clone,
         Op: 'static,
@@ -160,46 +174,172 @@ impl<T, Op: RelationalOp<T = T>> Relation<T, Op> {
     }
 }
 
-impl<K, V, Op: RelationalOp<T = (K, V)>> Relation<(K, V), Op> {
+impl<K, V, Op: RelationalOp<T = (K, V)>, S: BuildHasher + Default> Relation<(K, V), Op, S> {
+    #[track_caller]
     pub fn antijoin(
         self,
-        other: Relation<K, impl RelationalOp<T = K>>,
-    ) -> Relation<(K, V), impl RelationalOp<T = (K, V)>>
+        other: Relation<K, impl RelationalOp<T = K>, S>,
+    ) -> Relation<(K, V), impl RelationalOp<T = (K, V)>, S>
     where
         K: Clone + Eq + Hash,
         V: Clone + Eq + Hash,
     {
-        assert!(Rc::ptr_eq(
-            &self.current_commit_id,
-            &other.current_commit_id
-        ));
-        Relation::new(
-            ops::Antijoin::new(self.relation, other.relation),
-            self.current_commit_id,
-        )
+        self.try_antijoin(other).unwrap()
+    }
+    #[allow(clippy::type_complexity)]
+    pub fn try_antijoin(
+        self,
+        other: Relation<K, impl RelationalOp<T = K>, S>,
+    ) -> Result<Relation<(K, V), impl RelationalOp<T = (K, V)>, S>, ContextMismatch>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Eq + Hash,
+    {
+        self.check_same_context("antijoin", &other)?;
+        Ok(Relation::new(
+            ops::Antijoin::<_, _, _, S>::new(
+                self.relation,
+                L2Map::<_, _, _, 2, S>::new(),
+                other.relation,
+            ),
+            self.context,
+        ))
+    }
+    #[track_caller]
+    pub fn antijoin_spilled(
+        self,
+        other: Relation<K, impl RelationalOp<T = K>, S>,
+        config: &SpillConfig,
+    ) -> Relation<(K, V), impl RelationalOp<T = (K, V)>, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Eq + Hash + Codec,
+    {
+        self.try_antijoin_spilled(other, config).unwrap()
     }
+    #[allow(clippy::type_complexity)]
+    pub fn try_antijoin_spilled(
+        self,
+        other: Relation<K, impl RelationalOp<T = K>, S>,
+        config: &SpillConfig,
+    ) -> Result<Relation<(K, V), impl RelationalOp<T = (K, V)>, S>, ContextMismatch>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Eq + Hash + Codec,
+    {
+        self.check_same_context("antijoin_spilled", &other)?;
+        Ok(Relation::new(
+            ops::Antijoin::<_, _, _, S>::new(
+                self.relation,
+                SpillMap::<_, _, _, S>::new(config.clone()),
+                other.relation,
+            ),
+            self.context,
+        ))
+    }
+    #[allow(clippy::type_complexity)]
+    #[track_caller]
+    pub fn range_join<V2: Clone + Ord, R: RangeBounds<V2>>(
+        self,
+        other: Relation<(K, V2), impl RelationalOp<T = (K, V2)>, S>,
+        range: impl Fn(&V) -> R,
+    ) -> Relation<(K, (V, V2)), impl RelationalOp<T = (K, (V, V2))>, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Eq + Hash,
+    {
+        self.try_range_join(other, range).unwrap()
+    }
+    #[allow(clippy::type_complexity)]
+    pub fn try_range_join<V2: Clone + Ord, R: RangeBounds<V2>>(
+        self,
+        other: Relation<(K, V2), impl RelationalOp<T = (K, V2)>, S>,
+        range: impl Fn(&V) -> R,
+    ) -> Result<Relation<(K, (V, V2)), impl RelationalOp<T = (K, (V, V2))>, S>, ContextMismatch>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Eq + Hash,
+    {
+        self.check_same_context("range_join", &other)?;
+        Ok(Relation::new(
+            ops::RangeJoin::<_, _, _, _, _, _, _, S>::new(self.relation, other.relation, range),
+            self.context,
+        ))
+    }
+    #[allow(clippy::type_complexity)]
+    #[track_caller]
     pub fn join<V2>(
         self,
-        other: Relation<(K, V2), impl RelationalOp<T = (K, V2)>>,
-    ) -> Relation<(K, (V, V2)), impl RelationalOp<T = (K, (V, V2))>>
+        other: Relation<(K, V2), impl RelationalOp<T = (K, V2)>, S>,
+    ) -> Relation<(K, (V, V2)), impl RelationalOp<T = (K, (V, V2))>, S>
     where
         K: Clone + Eq + Hash,
         V: Clone + Eq + Hash,
         V2: Clone + Eq + Hash,
     {
-        assert!(Rc::ptr_eq(
-            &self.current_commit_id,
-            &other.current_commit_id
-        ));
-        Relation::new(
-            ops::Join::new(self.relation, other.relation),
-            self.current_commit_id,
-        )
+        self.try_join(other).unwrap()
+    }
+    #[allow(clippy::type_complexity)]
+    pub fn try_join<V2>(
+        self,
+        other: Relation<(K, V2), impl RelationalOp<T = (K, V2)>, S>,
+    ) -> Result<Relation<(K, (V, V2)), impl RelationalOp<T = (K, (V, V2))>, S>, ContextMismatch>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Eq + Hash,
+        V2: Clone + Eq + Hash,
+    {
+        self.check_same_context("join", &other)?;
+        Ok(Relation::new(
+            ops::Join::new(
+                self.relation,
+                L2Map::<_, _, _, 2, S>::new(),
+                other.relation,
+                L2Map::<_, _, _, 2, S>::new(),
+            ),
+            self.context,
+        ))
+    }
+    #[allow(clippy::type_complexity)]
+    #[track_caller]
+    pub fn join_spilled<V2>(
+        self,
+        other: Relation<(K, V2), impl RelationalOp<T = (K, V2)>, S>,
+        config: &SpillConfig,
+    ) -> Relation<(K, (V, V2)), impl RelationalOp<T = (K, (V, V2))>, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Eq + Hash + Codec,
+        V2: Clone + Eq + Hash + Codec,
+    {
+        self.try_join_spilled(other, config).unwrap()
+    }
+    #[allow(clippy::type_complexity)]
+    pub fn try_join_spilled<V2>(
+        self,
+        other: Relation<(K, V2), impl RelationalOp<T = (K, V2)>, S>,
+        config: &SpillConfig,
+    ) -> Result<Relation<(K, (V, V2)), impl RelationalOp<T = (K, (V, V2))>, S>, ContextMismatch>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Eq + Hash + Codec,
+        V2: Clone + Eq + Hash + Codec,
+    {
+        self.check_same_context("join_spilled", &other)?;
+        Ok(Relation::new(
+            ops::Join::new(
+                self.relation,
+                SpillMap::<_, _, _, S>::new(config.clone()),
+                other.relation,
+                SpillMap::<_, _, _, S>::new(config.clone()),
+            ),
+            self.context,
+        ))
     }
     pub fn join_values<V2>(
         self,
-        other: Relation<(K, V2), impl RelationalOp<T = (K, V2)>>,
-    ) -> Relation<(V, V2), impl RelationalOp<T = (V, V2)>>
+        other: Relation<(K, V2), impl RelationalOp<T = (K, V2)>, S>,
+    ) -> Relation<(V, V2), impl RelationalOp<T = (V, V2)>, S>
     where
         K: Clone + Eq + Hash,
         V: Clone + Eq + Hash,
@@ -209,8 +349,8 @@ impl<K, V, Op: RelationalOp<T = (K, V)>> Relation<(K, V), Op> {
     }
     pub fn semijoin(
         self,
-        other: Relation<K, impl RelationalOp<T = K>>,
-    ) -> Relation<(K, V), impl RelationalOp<T = (K, V)>>
+        other: Relation<K, impl RelationalOp<T = K>, S>,
+    ) -> Relation<(K, V), impl RelationalOp<T = (K, V)>, S>
     where
         K: Clone + Eq + Hash,
         V: Clone + Eq + Hash,
@@ -218,32 +358,123 @@ impl<K, V, Op: RelationalOp<T = (K, V)>> Relation<(K, V), Op> {
         self.join(other.map_h(|k| (k, ())))
             .map_h(|(k, (v, ()))| (k, v))
     }
-    pub fn fsts(self) -> Relation<K, impl RelationalOp<T = K>> {
+    pub fn fsts(self) -> Relation<K, impl RelationalOp<T = K>, S> {
         self.map_h(|(k, _)| k)
     }
-    pub fn snds(self) -> Relation<V, impl RelationalOp<T = V>> {
+    pub fn snds(self) -> Relation<V, impl RelationalOp<T = V>, S> {
         self.map_h(|(_, v)| v)
     }
+    #[allow(clippy::type_complexity)]
     pub fn top_ns<const N: usize>(
         self,
-    ) -> Relation<(K, ArrayVec<V, N>), impl RelationalOp<T = (K, ArrayVec<V, N>)>>
+    ) -> Relation<(K, ArrayVec<V, N>), impl RelationalOp<T = (K, ArrayVec<V, N>)>, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Ord + Hash,
+    {
+        Relation::new(
+            ops::TopNs::<_, _, _, _, N, S>::new(self.relation, Max),
+            self.context,
+        )
+    }
+    #[allow(clippy::type_complexity)]
+    pub fn top_n(self, n: usize) -> Relation<(K, Vec<V>), impl RelationalOp<T = (K, Vec<V>)>, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Ord + Hash,
+    {
+        Relation::new(
+            ops::TopN::<_, _, _, _, _, S>::new(
+                self.relation,
+                ops::InputOp::constant([(n, 1)]),
+                Max,
+            ),
+            self.context,
+        )
+    }
+    #[allow(clippy::type_complexity)]
+    pub fn bottom_n(self, n: usize) -> Relation<(K, Vec<V>), impl RelationalOp<T = (K, Vec<V>)>, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Ord + Hash,
+    {
+        Relation::new(
+            ops::TopN::<_, _, _, _, _, S>::new(
+                self.relation,
+                ops::InputOp::constant([(n, 1)]),
+                Min,
+            ),
+            self.context,
+        )
+    }
+    pub fn top_n_ranked(
+        self,
+        n: usize,
+    ) -> Relation<(K, usize, V), impl RelationalOp<T = (K, usize, V)>, S>
     where
         K: Clone + Eq + Hash,
         V: Clone + Ord + Hash,
     {
-        Relation::new(ops::TopNs::new(self.relation), self.current_commit_id)
+        self.top_n(n).flat_map(ranked)
     }
+    pub fn bottom_n_ranked(
+        self,
+        n: usize,
+    ) -> Relation<(K, usize, V), impl RelationalOp<T = (K, usize, V)>, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Ord + Hash,
+    {
+        self.bottom_n(n).flat_map(ranked)
+    }
+    #[allow(clippy::type_complexity)]
+    #[track_caller]
+    pub fn top_n_by(
+        self,
+        limit: Relation<usize, impl RelationalOp<T = usize>, S>,
+    ) -> Relation<(K, Vec<V>), impl RelationalOp<T = (K, Vec<V>)>, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Ord + Hash,
+    {
+        self.try_top_n_by(limit).unwrap()
+    }
+    #[allow(clippy::type_complexity)]
+    pub fn try_top_n_by(
+        self,
+        limit: Relation<usize, impl RelationalOp<T = usize>, S>,
+    ) -> Result<Relation<(K, Vec<V>), impl RelationalOp<T = (K, Vec<V>)>, S>, ContextMismatch>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Ord + Hash,
+    {
+        self.check_same_context("top_n_by", &limit)?;
+        Ok(Relation::new(
+            ops::TopN::<_, _, _, _, _, S>::new(self.relation, limit.relation, Max),
+            self.context,
+        ))
+    }
+    pub fn rank_by_key(self) -> Relation<(K, usize, V), impl RelationalOp<T = (K, usize, V)>, S>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone + Ord,
+    {
+        Relation::new(
+            ops::RankByKey::<_, _, _, S>::new(self.relation),
+            self.context,
+        )
+    }
+    #[allow(clippy::type_complexity)]
     pub fn random_ns<const N: usize>(
         self,
         seed: u64,
-    ) -> Relation<(K, ArrayVec<V, N>), impl RelationalOp<T = (K, ArrayVec<V, N>)>>
+    ) -> Relation<(K, ArrayVec<V, N>), impl RelationalOp<T = (K, ArrayVec<V, N>)>, S>
     where
         K: Clone + Eq + Hash,
         V: Clone + Ord + Hash,
     {
         self.map_h(move |x| {
-            let mut hasher = DefaultHasher::new();
-            hasher.write_u64(seed);
+            let mut hasher = SipHasher13::new_with_keys(0, seed);
             x.hash(&mut hasher);
             let (k, v) = x;
             (k, (hasher.finish(), v))
@@ -251,7 +482,7 @@ impl<K, V, Op: RelationalOp<T = (K, V)>> Relation<(K, V), Op> {
         .top_ns::<N>()
         .map_h(|(k, arr)| (k, arr.into_iter().map(|(_, v)| v).collect()))
     }
-    pub fn maxes(self) -> Relation<(K, V), impl RelationalOp<T = (K, V)>>
+    pub fn maxes(self) -> Relation<(K, V), impl RelationalOp<T = (K, V)>, S>
     where
         K: Clone + Eq + Hash,
         V: Clone + Ord + Hash,
@@ -259,38 +490,42 @@ impl<K, V, Op: RelationalOp<T = (K, V)>> Relation<(K, V), Op> {
         self.top_ns::<1>()
             .map_h(|(k, v)| (k, v.into_iter().next().unwrap()))
     }
-    pub fn mins(self) -> Relation<(K, V), impl RelationalOp<T = (K, V)>>
+    pub fn mins(self) -> Relation<(K, V), impl RelationalOp<T = (K, V)>, S>
     where
         K: Clone + Eq + Hash,
         V: Clone + Ord + Hash,
     {
-        self.map_h(|(k, v)| (k, Reverse(v)))
-            .maxes()
-            .map_h(|(k, Reverse(v))| (k, v))
+        Relation::new(
+            ops::TopNs::<_, _, _, _, 1, S>::new(self.relation, Min),
+            self.context,
+        )
+        .map_h(|(k, v)| (k, v.into_iter().next().unwrap()))
     }
     pub fn split(
         self,
     ) -> (
-        Relation<K, impl RelationalOp<T = K>>,
-        Relation<V, impl RelationalOp<T = V>>,
+        Relation<K, impl RelationalOp<T = K>, S>,
+        Relation<V, impl RelationalOp<T = V>, S>,
     ) {
         let (left, right) = ops::split(self.relation);
         (
-            Relation::new(left, Rc::clone(&self.current_commit_id)),
-            Relation::new(right, self.current_commit_id),
+            Relation::new(left, Rc::clone(&self.context)),
+            Relation::new(right, self.context),
         )
     }
-    pub fn swaps(self) -> Relation<(V, K), impl RelationalOp<T = (V, K)>> {
+    pub fn swaps(self) -> Relation<(V, K), impl RelationalOp<T = (V, K)>, S> {
         self.map_h(|(k, v)| (v, k))
     }
 }
 
-impl<L, R, Op: RelationalOp<T = Either<L, R>>> Relation<Either<L, R>, Op> {
+impl<L, R, Op: RelationalOp<T = Either<L, R>>, S: BuildHasher + Default>
+    Relation<Either<L, R>, Op, S>
+{
     pub fn partition(
         self,
     ) -> (
-        Relation<L, impl RelationalOp<T = L>>,
-        Relation<R, impl RelationalOp<T = R>>,
+        Relation<L, impl RelationalOp<T = L>, S>,
+        Relation<R, impl RelationalOp<T = R>, S>,
     ) {
         let (l, r) = self
             .map_h(|x| match x {
@@ -301,3 +536,9 @@ impl<L, R, Op: RelationalOp<T = Either<L, R>>> Relation<Either<L, R>, Op> {
         (l.flatten_h(), r.flatten_h())
     }
 }
+
+fn ranked<K: Clone, V>((k, vs): (K, Vec<V>)) -> impl Iterator<Item = (K, usize, V)> {
+    vs.into_iter()
+        .enumerate()
+        .map(move |(i, v)| (k.clone(), i, v))
+}
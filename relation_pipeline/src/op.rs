@@ -1,6 +1,6 @@
 use std::{
     collections::{HashMap, hash_map},
-    hash::Hash,
+    hash::{BuildHasher, Hash},
 };
 
 pub(crate) type CommitId = u64;
@@ -19,7 +19,7 @@ pub trait RelationalOp {
     {
         self.for_each(commit_id, |x, n| sender.send((x, n)));
     }
-    fn dump_to_map(&mut self, commit_id: u64, counts: &mut HashMap<Self::T, i64>)
+    fn dump_to_map<S: BuildHasher>(&mut self, commit_id: u64, counts: &mut HashMap<Self::T, i64, S>)
     where
         Self::T: Eq + Hash,
     {
@@ -1,54 +1,47 @@
 use std::{
     collections::{HashMap, hash_map},
-    hash::Hash,
+    hash::{BuildHasher, Hash},
 };
 
-use l2_map::L2Map;
-
 use crate::op::{CommitId, RelationalOp};
 
-use super::l2_util::add;
+use super::l2_util::GroupStore;
 
-pub(crate) struct Antijoin<
-    K: Clone + Eq + Hash,
-    V: Clone + Eq + Hash,
-    I: RelationalOp<T = (K, V)>,
-    J: RelationalOp<T = K>,
-> {
+pub(crate) struct Antijoin<I, J, M: GroupStore, S> {
     input1: I,
-    kvs1: L2Map<K, V, i64>,
+    kvs1: M,
     input2: J,
-    kvs2: HashMap<K, i64>,
+    kvs2: HashMap<M::K, i64, S>,
 }
 
-impl<K, V, I, J> Antijoin<K, V, I, J>
+impl<I, J, M, S> Antijoin<I, J, M, S>
 where
-    K: Clone + Eq + Hash,
-    V: Clone + Eq + Hash,
-    I: RelationalOp<T = (K, V)>,
-    J: RelationalOp<T = K>,
+    M: GroupStore<K: Clone + Eq + Hash>,
+    I: RelationalOp<T = (M::K, M::V)>,
+    J: RelationalOp<T = M::K>,
+    S: BuildHasher + Default,
 {
-    pub(crate) fn new(input1: I, input2: J) -> Self {
+    pub(crate) fn new(input1: I, kvs1: M, input2: J) -> Self {
         Self {
             input1,
-            kvs1: L2Map::new(),
+            kvs1,
             input2,
-            kvs2: HashMap::new(),
+            kvs2: HashMap::default(),
         }
     }
 }
 
-impl<K, V, I, J> RelationalOp for Antijoin<K, V, I, J>
+impl<I, J, M, S> RelationalOp for Antijoin<I, J, M, S>
 where
-    K: Clone + Eq + Hash,
-    V: Clone + Eq + Hash,
-    I: RelationalOp<T = (K, V)>,
-    J: RelationalOp<T = K>,
+    M: GroupStore<K: Clone + Eq + Hash, V: Clone>,
+    I: RelationalOp<T = (M::K, M::V)>,
+    J: RelationalOp<T = M::K>,
+    S: BuildHasher,
 {
-    type T = (K, V);
+    type T = (M::K, M::V);
     type Unconsolidated = Self;
 
-    fn for_each(&mut self, commit_id: CommitId, mut f: impl FnMut((K, V), i64)) {
+    fn for_each(&mut self, commit_id: CommitId, mut f: impl FnMut(Self::T, i64)) {
         self.input2.for_each(commit_id, |k, n2| {
             if n2 == 0 {
                 return;
@@ -59,15 +52,15 @@ where
                     *count += n2;
                     if *count == 0 {
                         let (k, _) = e.remove_entry();
-                        for (v1, n1) in self.kvs1.get_iter(&k) {
-                            f((k.clone(), v1.clone()), *n1);
-                        }
+                        self.kvs1.for_each_in(&k, |v1, n1| {
+                            f((k.clone(), v1.clone()), n1);
+                        });
                     }
                 }
                 hash_map::Entry::Vacant(e) => {
-                    for (v1, n1) in self.kvs1.get_iter(e.key()) {
-                        f((e.key().clone(), v1.clone()), -*n1);
-                    }
+                    self.kvs1.for_each_in(e.key(), |v1, n1| {
+                        f((e.key().clone(), v1.clone()), -n1);
+                    });
                     e.insert(n2);
                 }
             }
@@ -79,7 +72,7 @@ where
             if !self.kvs2.contains_key(&k) {
                 f((k.clone(), v1.clone()), n1);
             }
-            add(&mut self.kvs1, k, v1, n1);
+            self.kvs1.add(k, v1, n1);
         });
     }
     fn unconsolidate(self) -> Self::Unconsolidated {
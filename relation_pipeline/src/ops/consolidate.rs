@@ -1,29 +1,55 @@
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash, RandomState},
+};
+
+use indexmap::IndexMap;
 
 use crate::op::{CommitId, RelationalOp};
 
-pub struct Consolidate<T: Eq + Hash, Op: RelationalOp<T = T>> {
+pub struct Consolidate<T: Eq + Hash, Op: RelationalOp<T = T>, S: BuildHasher = RandomState> {
     relation: Op,
-    counts: HashMap<T, i64>,
+    counts: Counts<T, S>,
 }
 
-impl<T: Eq + Hash, Op: RelationalOp<T = T>> Consolidate<T, Op> {
-    pub(crate) fn new(relation: Op) -> Self {
-        Self {
-            relation,
-            counts: HashMap::new(),
-        }
+enum Counts<T, S> {
+    Unordered(HashMap<T, i64, S>),
+    // Emits in order of first occurrence within the commit, independent of hashing.
+    Ordered(IndexMap<T, i64, S>),
+}
+
+impl<T: Eq + Hash, Op: RelationalOp<T = T>, S: BuildHasher + Default> Consolidate<T, Op, S> {
+    pub(crate) fn new(relation: Op, deterministic: bool) -> Self {
+        let counts = if deterministic {
+            Counts::Ordered(IndexMap::default())
+        } else {
+            Counts::Unordered(HashMap::default())
+        };
+        Self { relation, counts }
     }
 }
 
-impl<T: Eq + Hash, Op: RelationalOp<T = T>> RelationalOp for Consolidate<T, Op> {
+impl<T: Eq + Hash, Op: RelationalOp<T = T>, S: BuildHasher> RelationalOp for Consolidate<T, Op, S> {
     type T = T;
     type Unconsolidated = Op;
 
     fn for_each(&mut self, commit_id: CommitId, mut f: impl FnMut(T, i64)) {
-        self.relation.dump_to_map(commit_id, &mut self.counts);
-        for (x, count) in self.counts.drain() {
-            f(x, count);
+        match &mut self.counts {
+            Counts::Unordered(counts) => {
+                self.relation.dump_to_map(commit_id, counts);
+                for (x, count) in counts.drain() {
+                    f(x, count);
+                }
+            }
+            Counts::Ordered(counts) => {
+                self.relation
+                    .for_each(commit_id, |x, n| *counts.entry(x).or_insert(0) += n);
+                for (x, count) in counts.drain(..) {
+                    if count != 0 {
+                        f(x, count);
+                    }
+                }
+            }
         }
     }
     fn unconsolidate(self) -> Self::Unconsolidated {
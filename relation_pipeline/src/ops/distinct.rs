@@ -1,22 +1,27 @@
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
 
 use crate::op::{CommitId, RelationalOp};
 
-pub(crate) struct Distinct<T: Clone + Eq + Hash, Op: RelationalOp<T = T>> {
+pub(crate) struct Distinct<T: Clone + Eq + Hash, Op: RelationalOp<T = T>, S: BuildHasher> {
     relation: Op,
-    counts: HashMap<T, i64>,
+    counts: HashMap<T, i64, S>,
 }
 
-impl<T: Clone + Eq + Hash, Op: RelationalOp<T = T>> Distinct<T, Op> {
+impl<T: Clone + Eq + Hash, Op: RelationalOp<T = T>, S: BuildHasher + Default> Distinct<T, Op, S> {
     pub fn new(relation: Op) -> Self {
         Self {
             relation,
-            counts: HashMap::new(),
+            counts: HashMap::default(),
         }
     }
 }
 
-impl<T: Clone + Eq + Hash, Op: RelationalOp<T = T>> RelationalOp for Distinct<T, Op> {
+impl<T: Clone + Eq + Hash, Op: RelationalOp<T = T>, S: BuildHasher> RelationalOp
+    for Distinct<T, Op, S>
+{
     type T = T;
     type Unconsolidated = Self;
 
@@ -1,5 +1,3 @@
-use std::{collections::HashMap, hash::Hash};
-
 use crate::op::{CommitId, RelationalOp};
 
 pub struct Dynamic<'a, T>(Box<dyn RelationalOpDyn<'a, T = T> + 'a>);
@@ -18,9 +16,6 @@ trait RelationalOpDyn<'a> {
         sender: &mut broadcast_channel::Sender<(Self::T, i64)>,
     ) where
         Self::T: Clone;
-    fn dump_to_map(&mut self, commit_id: u64, counts: &mut HashMap<Self::T, i64>)
-    where
-        Self::T: Eq + Hash;
     fn unconsolidate(self: Box<Self>) -> Box<dyn RelationalOpDyn<'a, T = Self::T> + 'a>;
 }
 
@@ -41,12 +36,6 @@ where
     {
         self.send_all(commit_id, sender);
     }
-    fn dump_to_map(&mut self, commit_id: u64, counts: &mut HashMap<Self::T, i64>)
-    where
-        Self::T: Eq + Hash,
-    {
-        self.dump_to_map(commit_id, counts);
-    }
     fn unconsolidate(self: Box<Self>) -> Box<dyn RelationalOpDyn<'a, T = Self::T> + 'a> {
         Box::new((*self).unconsolidate())
     }
@@ -68,12 +57,6 @@ impl<T> RelationalOp for Dynamic<'_, T> {
     {
         self.0.send_all(commit_id, sender);
     }
-    fn dump_to_map(&mut self, commit_id: u64, counts: &mut HashMap<Self::T, i64>)
-    where
-        T: Eq + Hash,
-    {
-        self.0.dump_to_map(commit_id, counts);
-    }
     fn unconsolidate(self) -> Self::Unconsolidated {
         Self(self.0.unconsolidate())
     }
@@ -1,18 +1,28 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, mem};
 
 use crate::op::{CommitId, RelationalOp};
 
 pub struct InputOp<T> {
+    initial: Vec<(T, i64)>,
     receiver: swap_channel::Receiver<(T, CommitId, i64)>,
     pending: VecDeque<(T, CommitId, i64)>,
 }
 impl<T> InputOp<T> {
-    pub(crate) fn new(receiver: swap_channel::Receiver<(T, CommitId, i64)>) -> Self {
+    pub(crate) fn new(
+        initial: Vec<(T, i64)>,
+        receiver: swap_channel::Receiver<(T, CommitId, i64)>,
+    ) -> Self {
         InputOp {
+            initial,
             receiver,
             pending: VecDeque::new(),
         }
     }
+
+    pub(crate) fn constant(values: impl IntoIterator<Item = (T, i64)>) -> Self {
+        let (_sender, receiver) = swap_channel::new();
+        Self::new(Vec::from_iter(values), receiver)
+    }
 }
 
 impl<T> RelationalOp for InputOp<T> {
@@ -20,6 +30,9 @@ impl<T> RelationalOp for InputOp<T> {
     type Unconsolidated = Self;
 
     fn for_each(&mut self, commit_id: CommitId, mut f: impl FnMut(T, i64)) {
+        for (x, n) in mem::take(&mut self.initial) {
+            f(x, n);
+        }
         while let Some(&(_, id, _)) = self.pending.front() {
             if id > commit_id {
                 return;
@@ -1,70 +1,56 @@
-use std::hash::Hash;
-
-use l2_map::L2Map;
-
 use crate::op::{CommitId, RelationalOp};
 
-use super::l2_util::add;
+use super::l2_util::GroupStore;
 
-pub(crate) struct Join<
-    K: Clone + Eq + Hash,
-    V1: Clone + Eq + Hash,
-    V2: Clone + Eq + Hash,
-    I: RelationalOp<T = (K, V1)>,
-    J: RelationalOp<T = (K, V2)>,
-> {
+pub(crate) struct Join<I, J, M1, M2> {
     input1: I,
-    kvs1: L2Map<K, V1, i64>,
+    kvs1: M1,
     input2: J,
-    kvs2: L2Map<K, V2, i64>,
+    kvs2: M2,
 }
 
-impl<K, V1, V2, I, J> Join<K, V1, V2, I, J>
+impl<I, J, M1, M2> Join<I, J, M1, M2>
 where
-    K: Clone + Eq + Hash,
-    V1: Clone + Eq + Hash,
-    V2: Clone + Eq + Hash,
-    I: RelationalOp<T = (K, V1)>,
-    J: RelationalOp<T = (K, V2)>,
+    M1: GroupStore,
+    M2: GroupStore<K = M1::K>,
+    I: RelationalOp<T = (M1::K, M1::V)>,
+    J: RelationalOp<T = (M1::K, M2::V)>,
 {
-    pub(crate) fn new(input1: I, input2: J) -> Self {
+    pub(crate) fn new(input1: I, kvs1: M1, input2: J, kvs2: M2) -> Self {
         Self {
             input1,
-            kvs1: L2Map::new(),
+            kvs1,
             input2,
-            kvs2: L2Map::new(),
+            kvs2,
         }
     }
 }
 
-impl<K, V1, V2, I, J> RelationalOp for Join<K, V1, V2, I, J>
+impl<I, J, M1, M2> RelationalOp for Join<I, J, M1, M2>
 where
-    K: Clone + Eq + Hash,
-    V1: Clone + Eq + Hash,
-    V2: Clone + Eq + Hash,
-    I: RelationalOp<T = (K, V1)>,
-    J: RelationalOp<T = (K, V2)>,
+    M1: GroupStore<K: Clone, V: Clone>,
+    M2: GroupStore<K = M1::K, V: Clone>,
+    I: RelationalOp<T = (M1::K, M1::V)>,
+    J: RelationalOp<T = (M1::K, M2::V)>,
 {
-    type T = (K, (V1, V2));
+    type T = (M1::K, (M1::V, M2::V));
     type Unconsolidated = Self;
 
-    fn for_each(&mut self, commit_id: CommitId, mut f: impl FnMut((K, (V1, V2)), i64)) {
+    fn for_each(&mut self, commit_id: CommitId, mut f: impl FnMut(Self::T, i64)) {
         self.input1.for_each(commit_id, |(k, v1), n1| {
             if n1 == 0 {
                 return;
             }
-            let v2s = self.kvs2.get_iter(&k);
-            for (v2, n2) in v2s {
-                f((k.clone(), (v1.clone(), v2.clone())), n1 * *n2);
-            }
-            add(&mut self.kvs1, k, v1, n1);
+            self.kvs2.for_each_in(&k, |v2, n2| {
+                f((k.clone(), (v1.clone(), v2.clone())), n1 * n2);
+            });
+            self.kvs1.add(k, v1, n1);
         });
         self.input2.for_each(commit_id, |(k, v2), n2| {
-            let v1s = self.kvs1.get_iter(&k);
-            for (v1, n1) in v1s {
-                f((k.clone(), (v1.clone(), v2.clone())), *n1 * n2);
-            }
-            add(&mut self.kvs2, k, v2, n2);
+            self.kvs1.for_each_in(&k, |v1, n1| {
+                f((k.clone(), (v1.clone(), v2.clone())), n1 * n2);
+            });
+            self.kvs2.add(k, v2, n2);
         });
     }
     fn unconsolidate(self) -> Self::Unconsolidated {
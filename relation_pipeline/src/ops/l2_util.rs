@@ -1,11 +1,89 @@
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
 
-use l2_map::L2Map;
+use l2_map::{L2Map, OrderedL2Map};
+use spill_map::{Codec, SpillMap};
 
-pub(super) fn add<K, V>(kvs: &mut L2Map<K, V, i64>, k: K, v: V, n1: i64)
+// Per-key multiset of values, as kept by the join operators for each side.
+pub(crate) trait GroupStore {
+    type K;
+    type V;
+
+    fn add(&mut self, k: Self::K, v: Self::V, n: i64);
+    fn for_each_in(&mut self, k: &Self::K, f: impl FnMut(&Self::V, i64));
+}
+
+impl<K, V, S> GroupStore for L2Map<K, V, i64, 2, S>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+    S: BuildHasher,
+{
+    type K = K;
+    type V = V;
+
+    fn add(&mut self, k: K, v: V, n: i64) {
+        add(self, k, v, n);
+    }
+    fn for_each_in(&mut self, k: &K, mut f: impl FnMut(&V, i64)) {
+        for (v, n) in self.get_iter(k) {
+            f(v, *n);
+        }
+    }
+}
+
+impl<K, V, S> GroupStore for SpillMap<K, V, i64, S>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash + Codec,
+    S: BuildHasher,
+{
+    type K = K;
+    type V = V;
+
+    fn add(&mut self, k: K, v: V, n: i64) {
+        match self.get_mut(&k, &v) {
+            Some(count) => {
+                *count += n;
+                if *count == 0 {
+                    self.remove(&k, &v);
+                }
+            }
+            None => {
+                self.insert(k, v, n);
+            }
+        }
+    }
+    fn for_each_in(&mut self, k: &K, mut f: impl FnMut(&V, i64)) {
+        for (v, n) in self.get_iter(k) {
+            f(v, *n);
+        }
+    }
+}
+
+pub(super) fn add<K, V, S>(kvs: &mut L2Map<K, V, i64, 2, S>, k: K, v: V, n1: i64)
 where
     K: Clone + Eq + Hash,
     V: Clone + Eq + Hash,
+    S: BuildHasher,
+{
+    match kvs.get_mut(&k, &v) {
+        Some(n) => {
+            *n += n1;
+            if *n == 0 {
+                kvs.remove(&k, &v);
+            }
+        }
+        None => {
+            kvs.insert(k, v, n1);
+        }
+    }
+}
+
+pub(super) fn add_ordered<K, V, S>(kvs: &mut OrderedL2Map<K, V, i64, S>, k: K, v: V, n1: i64)
+where
+    K: Eq + Hash,
+    V: Ord,
+    S: BuildHasher,
 {
     match kvs.get_mut(&k, &v) {
         Some(n) => {
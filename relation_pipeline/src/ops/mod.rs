@@ -5,7 +5,10 @@ pub(crate) use counts::Counts;
 pub(crate) use distinct::Distinct;
 pub(crate) use flat_map::FlatMap;
 pub(crate) use join::Join;
+pub(crate) use range_join::RangeJoin;
+pub(crate) use rank_by_key::RankByKey;
 pub(crate) use split::split;
+pub(crate) use top_n::TopN;
 pub(crate) use top_ns::TopNs;
 
 pub use dynamic::Dynamic;
@@ -22,6 +25,9 @@ mod flat_map;
 mod input;
 mod join;
 mod l2_util;
+mod range_join;
+mod rank_by_key;
 mod save;
 mod split;
+mod top_n;
 mod top_ns;
@@ -0,0 +1,93 @@
+use std::{
+    hash::{BuildHasher, Hash},
+    ops::RangeBounds,
+};
+
+use l2_map::{L2Map, OrderedL2Map};
+
+use crate::op::{CommitId, RelationalOp};
+
+use super::l2_util::{add, add_ordered};
+
+pub(crate) struct RangeJoin<K, V1, V2, R, F, I, J, S>
+where
+    K: Clone + Eq + Hash,
+    V1: Clone + Eq + Hash,
+    V2: Clone + Ord,
+    R: RangeBounds<V2>,
+    F: Fn(&V1) -> R,
+    I: RelationalOp<T = (K, V1)>,
+    J: RelationalOp<T = (K, V2)>,
+    S: BuildHasher,
+{
+    input1: I,
+    kvs1: L2Map<K, V1, i64, 2, S>,
+    input2: J,
+    kvs2: OrderedL2Map<K, V2, i64, S>,
+    range: F,
+}
+
+impl<K, V1, V2, R, F, I, J, S> RangeJoin<K, V1, V2, R, F, I, J, S>
+where
+    K: Clone + Eq + Hash,
+    V1: Clone + Eq + Hash,
+    V2: Clone + Ord,
+    R: RangeBounds<V2>,
+    F: Fn(&V1) -> R,
+    I: RelationalOp<T = (K, V1)>,
+    J: RelationalOp<T = (K, V2)>,
+    S: BuildHasher + Default,
+{
+    pub(crate) fn new(input1: I, input2: J, range: F) -> Self {
+        Self {
+            input1,
+            kvs1: L2Map::new(),
+            input2,
+            kvs2: OrderedL2Map::new(),
+            range,
+        }
+    }
+}
+
+impl<K, V1, V2, R, F, I, J, S> RelationalOp for RangeJoin<K, V1, V2, R, F, I, J, S>
+where
+    K: Clone + Eq + Hash,
+    V1: Clone + Eq + Hash,
+    V2: Clone + Ord,
+    R: RangeBounds<V2>,
+    F: Fn(&V1) -> R,
+    I: RelationalOp<T = (K, V1)>,
+    J: RelationalOp<T = (K, V2)>,
+    S: BuildHasher,
+{
+    type T = (K, (V1, V2));
+    type Unconsolidated = Self;
+
+    fn for_each(&mut self, commit_id: CommitId, mut f: impl FnMut((K, (V1, V2)), i64)) {
+        self.input1.for_each(commit_id, |(k, v1), n1| {
+            if n1 == 0 {
+                return;
+            }
+            for (v2, n2) in self.kvs2.range(&k, (self.range)(&v1)) {
+                f((k.clone(), (v1.clone(), v2.clone())), n1 * *n2);
+            }
+            add(&mut self.kvs1, k, v1, n1);
+        });
+        self.input2.for_each(commit_id, |(k, v2), n2| {
+            if n2 == 0 {
+                return;
+            }
+            // The range is a function of the left value, so the left side can't be
+            // range-scanned here; filter the key's group instead.
+            for (v1, n1) in self.kvs1.get_iter(&k) {
+                if (self.range)(v1).contains(&v2) {
+                    f((k.clone(), (v1.clone(), v2.clone())), *n1 * n2);
+                }
+            }
+            add_ordered(&mut self.kvs2, k, v2, n2);
+        });
+    }
+    fn unconsolidate(self) -> Self::Unconsolidated {
+        self
+    }
+}
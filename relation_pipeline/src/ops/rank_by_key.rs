@@ -0,0 +1,75 @@
+use std::{
+    hash::{BuildHasher, Hash},
+    ops::Bound,
+};
+
+use l2_map::OrderedL2Map;
+
+use crate::op::{CommitId, RelationalOp};
+
+use super::l2_util::add_ordered;
+
+pub(crate) struct RankByKey<
+    K: Clone + Eq + Hash,
+    V: Clone + Ord,
+    Op: RelationalOp<T = (K, V)>,
+    S: BuildHasher,
+> {
+    relation: Op,
+    values: OrderedL2Map<K, V, i64, S>,
+}
+
+impl<K, V, Op, S> RankByKey<K, V, Op, S>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Ord,
+    Op: RelationalOp<T = (K, V)>,
+    S: BuildHasher + Default,
+{
+    pub(crate) fn new(relation: Op) -> Self {
+        Self {
+            relation,
+            values: OrderedL2Map::new(),
+        }
+    }
+}
+
+impl<K, V, Op, S> RelationalOp for RankByKey<K, V, Op, S>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Ord,
+    Op: RelationalOp<T = (K, V)>,
+    S: BuildHasher,
+{
+    type T = (K, usize, V);
+    type Unconsolidated = Self;
+
+    fn for_each(&mut self, commit_id: CommitId, mut f: impl FnMut((K, usize, V), i64)) {
+        let values = &mut self.values;
+        self.relation.for_each(commit_id, |(k, v), count| {
+            if count == 0 {
+                return;
+            }
+            let was_present = values.get(&k, &v).is_some();
+            add_ordered(values, k.clone(), v.clone(), count);
+            let is_present = values.get(&k, &v).is_some();
+            if was_present == is_present {
+                return;
+            }
+            let rank = values.range(&k, ..v.clone()).count();
+            let (shift_from, shift_to) = if is_present { (0, 1) } else { (1, 0) };
+            for (i, w) in values
+                .range(&k, (Bound::Excluded(v.clone()), Bound::Unbounded))
+                .map(|(w, _)| w)
+                .enumerate()
+            {
+                f((k.clone(), rank + i + shift_from, w.clone()), -1);
+                f((k.clone(), rank + i + shift_to, w.clone()), 1);
+            }
+            f((k, rank, v), if is_present { 1 } else { -1 });
+        })
+    }
+    fn unconsolidate(self) -> Self::Unconsolidated {
+        self
+    }
+}
@@ -1,11 +1,13 @@
 use std::{
-    cell::{Cell, RefCell},
-    hash::Hash,
+    cell::RefCell,
+    hash::{BuildHasher, Hash, RandomState},
+    marker::PhantomData,
     rc::Rc,
 };
 
 use crate::{
-    Relation,
+    IndexedOutput, Output, Relation,
+    context::ContextState,
     op::{CommitId, RelationalOp},
 };
 
@@ -47,13 +49,14 @@ impl<T: Clone, R: RelationalOp<T = T>> RelationalOp for SaveOp<T, R> {
     }
 }
 
-pub struct Save<T: Clone, R: RelationalOp<T = T> = Dynamic<'static, T>> {
+pub struct Save<T: Clone, R: RelationalOp<T = T> = Dynamic<'static, T>, S = RandomState> {
     inner: Rc<RefCell<SaveInner<T, R>>>,
-    current_commit_id: Rc<Cell<u64>>,
+    context: Rc<ContextState>,
+    hasher: PhantomData<fn() -> S>,
 }
 
-impl<T: Clone, Op: RelationalOp<T = T>> Save<T, Op> {
-    pub(crate) fn new(relation: Op, commit_id: Rc<Cell<u64>>) -> Self {
+impl<T: Clone, Op: RelationalOp<T = T>, S> Save<T, Op, S> {
+    pub(crate) fn new(relation: Op, context: Rc<ContextState>) -> Self {
         let sender = broadcast_channel::Sender::new();
         let inner = SaveInner {
             relation,
@@ -62,21 +65,33 @@ impl<T: Clone, Op: RelationalOp<T = T>> Save<T, Op> {
         };
         Save {
             inner: Rc::new(RefCell::new(inner)),
-            current_commit_id: commit_id,
+            context,
+            hasher: PhantomData,
         }
     }
-    pub fn get_(&self) -> Relation<T, SaveOp<T, Op>> {
+    pub fn get_(&self) -> Relation<T, SaveOp<T, Op>, S> {
         let input = self.inner.clone();
         let receiver = self.inner.borrow().sender.subscribe();
-        Relation::new(
-            SaveOp { input, receiver },
-            Rc::clone(&self.current_commit_id),
-        )
+        Relation::new(SaveOp { input, receiver }, Rc::clone(&self.context))
     }
-    pub fn get(&self) -> Relation<T, Consolidate<T, SaveOp<T, Op>>>
+    pub fn get(&self) -> Relation<T, Consolidate<T, SaveOp<T, Op>, S>, S>
     where
         T: Eq + Hash,
+        S: BuildHasher + Default,
     {
         self.get_().consolidate()
     }
+    pub fn subscriber_count(&self) -> usize {
+        self.inner.borrow().sender.receiver_count()
+    }
+    // Updates buffered for each live subscriber that it has not yet read.
+    pub fn backlogs(&self) -> Vec<usize> {
+        self.inner.borrow().sender.backlogs()
+    }
+}
+
+impl<K: Clone + Ord, V: Clone + Ord, Op: RelationalOp<T = (K, V)>, S> Save<(K, V), Op, S> {
+    pub fn indexed(&self) -> IndexedOutput<K, V, SaveOp<(K, V), Op>, S> {
+        IndexedOutput::new(Output(self.get_()))
+    }
 }
@@ -0,0 +1,181 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hash},
+};
+
+use l2_heaps::{HeapOrder, L2Heaps};
+
+use crate::op::{CommitId, RelationalOp};
+
+pub(crate) struct TopN<
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+    Op: RelationalOp<T = (K, V)>,
+    L: RelationalOp<T = usize>,
+    O: HeapOrder<V>,
+    S: BuildHasher,
+> {
+    relation: Op,
+    limits: L,
+    limit_counts: BTreeMap<usize, i64>,
+    limit: usize,
+    tops: HashMap<K, Vec<(V, i64)>, S>,
+    heaps: L2Heaps<K, V, i64, O, 2, S>,
+}
+
+impl<K, V, Op, L, O, S> TopN<K, V, Op, L, O, S>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+    Op: RelationalOp<T = (K, V)>,
+    L: RelationalOp<T = usize>,
+    O: HeapOrder<V>,
+    S: BuildHasher + Default,
+{
+    pub(crate) fn new(relation: Op, limits: L, order: O) -> Self {
+        Self {
+            relation,
+            limits,
+            limit_counts: BTreeMap::new(),
+            limit: 0,
+            tops: HashMap::default(),
+            heaps: L2Heaps::with_order(order),
+        }
+    }
+}
+
+impl<K, V, Op, L, O, S> TopN<K, V, Op, L, O, S>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+    Op: RelationalOp<T = (K, V)>,
+    L: RelationalOp<T = usize>,
+    O: HeapOrder<V>,
+    S: BuildHasher,
+{
+    fn update_limit(&mut self, commit_id: CommitId, mut f: impl FnMut((K, Vec<V>), i64)) {
+        let limit_counts = &mut self.limit_counts;
+        self.limits.for_each(commit_id, |limit, count| {
+            let current = limit_counts.entry(limit).or_insert(0);
+            *current += count;
+            if *current == 0 {
+                limit_counts.remove(&limit);
+            }
+        });
+        let new_limit = self.limit_counts.keys().next_back().copied().unwrap_or(0);
+        if new_limit == self.limit {
+            return;
+        }
+        for (k, vec) in self.tops.iter_mut() {
+            if new_limit < self.limit {
+                if vec.len() <= new_limit {
+                    continue;
+                }
+                f((k.clone(), output(vec)), -1);
+                for (v, count) in vec.drain(new_limit..) {
+                    self.heaps.insert(k.clone(), v, count);
+                }
+            } else {
+                if self.heaps.get_max(k).is_none() {
+                    continue;
+                }
+                if !vec.is_empty() {
+                    f((k.clone(), output(vec)), -1);
+                }
+                while vec.len() < new_limit {
+                    let Some(next) = self.heaps.pop_max(k) else {
+                        break;
+                    };
+                    vec.push(next);
+                }
+            }
+            if !vec.is_empty() {
+                f((k.clone(), output(vec)), 1);
+            }
+        }
+        self.limit = new_limit;
+    }
+}
+
+impl<K, V, Op, L, O, S> RelationalOp for TopN<K, V, Op, L, O, S>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+    Op: RelationalOp<T = (K, V)>,
+    L: RelationalOp<T = usize>,
+    O: HeapOrder<V>,
+    S: BuildHasher,
+{
+    type T = (K, Vec<V>);
+    type Unconsolidated = Self;
+
+    fn for_each(&mut self, commit_id: CommitId, mut f: impl FnMut((K, Vec<V>), i64)) {
+        self.update_limit(commit_id, &mut f);
+        let limit = self.limit;
+        self.relation.for_each(commit_id, |(k, v), count| {
+            if count == 0 {
+                return;
+            }
+            let mut entry = hashmap_tools::or_default(self.tops.entry(k.clone()));
+            let vec = entry.get_mut();
+            if let Some(i) = vec.iter().position(|(x, _)| *x == v) {
+                let cur_count = &mut vec[i].1;
+                *cur_count += count;
+                if *cur_count == 0 {
+                    f((k.clone(), output(vec)), -1);
+                    vec.remove(i);
+                    if let Some(next) = self.heaps.pop_max(&k) {
+                        vec.push(next);
+                    }
+                    if vec.is_empty() {
+                        entry.remove();
+                    } else {
+                        f((k, output(vec)), 1);
+                    }
+                }
+                return;
+            }
+            if vec.len() < limit {
+                if !vec.is_empty() {
+                    f((k.clone(), output(vec)), -1);
+                }
+                vec.push((v, count));
+                vec.sort_by(|(a, _), (b, _)| self.heaps.order().cmp(b, a));
+                f((k, output(vec)), 1);
+                return;
+            }
+            let mut to_insert = (v, count);
+            if let Some(cur_back) = vec.last()
+                && self.heaps.order().cmp(&cur_back.0, &to_insert.0) == Ordering::Less
+            {
+                f((k.clone(), output(vec)), -1);
+                std::mem::swap(vec.last_mut().unwrap(), &mut to_insert);
+                vec.sort_by(|(a, _), (b, _)| self.heaps.order().cmp(b, a));
+                f((k.clone(), output(vec)), 1);
+            }
+            let (v, count) = to_insert;
+            match self.heaps.get_mut(&k, &v) {
+                Some(current_count) => {
+                    *current_count += count;
+                    if *current_count == 0 {
+                        self.heaps.remove(&k, &v);
+                    }
+                }
+                None => {
+                    self.heaps.insert(k.clone(), v, count);
+                }
+            }
+            if vec.is_empty() && self.heaps.get_max(&k).is_none() {
+                entry.remove();
+            }
+        })
+    }
+    fn unconsolidate(self) -> Self::Unconsolidated {
+        self
+    }
+}
+
+fn output<V: Clone>(vec: &[(V, i64)]) -> Vec<V> {
+    vec.iter().map(|(v, _)| v.clone()).collect()
+}
@@ -1,34 +1,52 @@
-use std::{collections::HashMap, hash::Hash, mem};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    hash::{BuildHasher, Hash, RandomState},
+    mem,
+};
 
 use arrayvec::ArrayVec;
+use l2_heaps::{HeapOrder, L2Heaps, Max};
 
 use crate::op::{CommitId, RelationalOp};
 
 pub(crate) struct TopNs<
     K: Clone + Eq + Hash,
-    V: Clone + Ord + Hash,
+    V: Clone + Eq + Hash,
     Op: RelationalOp<T = (K, V)>,
+    O: HeapOrder<V> = Max,
     const N: usize = 1,
+    S: BuildHasher = RandomState,
 > {
     relation: Op,
-    tops: HashMap<K, ArrayVec<(V, i64), N>>,
-    heaps: l2_heaps::L2Heaps<K, V, i64>,
+    tops: HashMap<K, ArrayVec<(V, i64), N>, S>,
+    heaps: L2Heaps<K, V, i64, O, 2, S>,
 }
 
-impl<K: Clone + Eq + Hash, V: Clone + Ord + Hash, Op: RelationalOp<T = (K, V)>, const N: usize>
-    TopNs<K, V, Op, N>
+impl<K, V, Op, O, const N: usize, S> TopNs<K, V, Op, O, N, S>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+    Op: RelationalOp<T = (K, V)>,
+    O: HeapOrder<V>,
+    S: BuildHasher + Default,
 {
-    pub fn new(relation: Op) -> Self {
+    pub fn new(relation: Op, order: O) -> Self {
         Self {
             relation,
-            tops: HashMap::new(),
-            heaps: l2_heaps::L2Heaps::new(),
+            tops: HashMap::default(),
+            heaps: L2Heaps::with_order(order),
         }
     }
 }
 
-impl<K: Clone + Eq + Hash, V: Clone + Ord + Hash, Op: RelationalOp<T = (K, V)>, const N: usize>
-    RelationalOp for TopNs<K, V, Op, N>
+impl<K, V, Op, O, const N: usize, S> RelationalOp for TopNs<K, V, Op, O, N, S>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+    Op: RelationalOp<T = (K, V)>,
+    O: HeapOrder<V>,
+    S: BuildHasher,
 {
     type T = (K, ArrayVec<V, N>);
     type Unconsolidated = Self;
@@ -62,16 +80,16 @@ impl<K: Clone + Eq + Hash, V: Clone + Ord + Hash, Op: RelationalOp<T = (K, V)>,
                     f((k.clone(), output(vec)), -1);
                 }
                 vec.push((v.clone(), count));
-                vec.sort_by(|(a, _), (b, _)| b.cmp(a));
+                vec.sort_by(|(a, _), (b, _)| self.heaps.order().cmp(b, a));
                 f((k, output(vec)), 1);
             } else {
                 let mut to_insert = (v, count);
                 let cur_back = vec.last().unwrap();
-                if cur_back.0 < to_insert.0 {
+                if self.heaps.order().cmp(&cur_back.0, &to_insert.0) == Ordering::Less {
                     f((k.clone(), output(vec)), -1);
                     let cur_back = vec.last_mut().unwrap();
                     mem::swap(cur_back, &mut to_insert);
-                    vec.sort_by(|(a, _), (b, _)| b.cmp(a));
+                    vec.sort_by(|(a, _), (b, _)| self.heaps.order().cmp(b, a));
                     f((k.clone(), output(vec)), 1);
                 }
                 let (v, count) = to_insert;
@@ -1,16 +1,56 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hash, RandomState},
+};
+
+use indexmap::{IndexMap, map};
+
 use crate::{Relation, RelationalOp, ops::Dynamic};
 
-pub struct Output<T, Op: RelationalOp<T = T> = Dynamic<'static, T>>(pub(crate) Relation<T, Op>);
+pub struct Output<T, Op: RelationalOp<T = T> = Dynamic<'static, T>, S = RandomState>(
+    pub(crate) Relation<T, Op, S>,
+);
 
-impl<T, Op: RelationalOp<T = T>> Output<T, Op> {
+impl<T, Op: RelationalOp<T = T>, S> Output<T, Op, S> {
     pub fn for_each(&mut self, f: impl FnMut(T, i64)) {
         self.0.for_each(f)
     }
 
-    pub fn dump_to_map(&mut self, counts: &mut std::collections::HashMap<T, i64>)
+    pub fn for_each_sorted(&mut self, mut f: impl FnMut(T, i64))
+    where
+        T: Ord,
+    {
+        let mut counts = BTreeMap::new();
+        self.0.for_each(|x, n| *counts.entry(x).or_insert(0) += n);
+        for (x, n) in counts {
+            if n != 0 {
+                f(x, n);
+            }
+        }
+    }
+
+    pub fn dump_to_map<S2: BuildHasher>(&mut self, counts: &mut HashMap<T, i64, S2>)
     where
-        T: Eq + std::hash::Hash,
+        T: Eq + Hash,
     {
         self.0.dump_to_map(counts)
     }
+
+    pub fn dump_to_index_map<S2: BuildHasher>(&mut self, counts: &mut IndexMap<T, i64, S2>)
+    where
+        T: Eq + Hash,
+    {
+        self.0.for_each(|x, n| match counts.entry(x) {
+            map::Entry::Vacant(e) => {
+                e.insert(n);
+            }
+            map::Entry::Occupied(mut e) => {
+                let count = e.get_mut();
+                *count += n;
+                if *count == 0 {
+                    e.swap_remove();
+                }
+            }
+        });
+    }
 }
@@ -1,6 +1,13 @@
-use std::{cell::Cell, rc::Rc};
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash, RandomState},
+    marker::PhantomData,
+    rc::Rc,
+};
 
 use crate::{
+    ContextId, ContextMismatch, Operand,
+    context::ContextState,
     op::{CommitId, RelationalOp},
     ops::Dynamic,
 };
@@ -9,37 +16,61 @@ pub(crate) struct RelationInner<T, Op: RelationalOp<T = T>> {
     pub(crate) op: Op,
 }
 
-pub struct Relation<T, Op: RelationalOp<T = T> = Dynamic<'static, T>> {
+pub struct Relation<T, Op: RelationalOp<T = T> = Dynamic<'static, T>, S = RandomState> {
     pub(crate) relation: RelationInner<T, Op>,
-    pub(crate) current_commit_id: Rc<Cell<u64>>,
+    pub(crate) context: Rc<ContextState>,
+    pub(crate) hasher: PhantomData<fn() -> S>,
 }
 
-impl<T, Op: RelationalOp<T = T>> Relation<T, Op> {
-    pub(crate) fn new(op: Op, commit_id: Rc<Cell<u64>>) -> Self {
+impl<T, Op: RelationalOp<T = T>, S> Relation<T, Op, S> {
+    pub(crate) fn new(op: Op, context: Rc<ContextState>) -> Self {
         Self {
             relation: RelationInner::new(op),
-            current_commit_id: commit_id,
+            context,
+            hasher: PhantomData,
         }
     }
 
+    pub fn context_id(&self) -> ContextId {
+        ContextId::of(&self.context)
+    }
+
+    pub(crate) fn check_same_context<U, Other: RelationalOp<T = U>>(
+        &self,
+        operation: &'static str,
+        other: &Relation<U, Other, S>,
+    ) -> Result<(), ContextMismatch> {
+        ContextMismatch::check(
+            operation,
+            Operand::Other,
+            self.context_id(),
+            other.context_id(),
+        )
+    }
+
     pub(crate) fn for_each(&mut self, f: impl FnMut(T, i64)) {
-        self.relation.for_each(self.current_commit_id.get(), f);
+        self.relation.for_each(self.context.commit_id.get(), f);
     }
 
-    pub(crate) fn dump_to_map(&mut self, counts: &mut std::collections::HashMap<T, i64>)
+    pub(crate) fn dump_to_map<S2: BuildHasher>(&mut self, counts: &mut HashMap<T, i64, S2>)
     where
-        T: Eq + std::hash::Hash,
+        T: Eq + Hash,
     {
         self.relation
             .op
-            .dump_to_map(self.current_commit_id.get(), counts);
+            .dump_to_map(self.context.commit_id.get(), counts);
     }
-    pub fn unconsolidate(self) -> Relation<T, Op::Unconsolidated> {
+    pub fn unconsolidate(self) -> Relation<T, Op::Unconsolidated, S> {
         Relation {
             relation: self.relation.unconsolidate(),
-            current_commit_id: self.current_commit_id,
+            context: self.context,
+            hasher: PhantomData,
         }
     }
+
+    pub fn with_hasher<S2>(self) -> Relation<T, Op, S2> {
+        Relation::new(self.relation.op, self.context)
+    }
 }
 
 impl<T, Op: RelationalOp<T = T>> RelationInner<T, Op> {
@@ -0,0 +1,60 @@
+use std::{
+    hash::Hash,
+    io::{Read, Write},
+};
+
+use bincode::error::{DecodeError, EncodeError};
+use indexmap::IndexMap;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{Input, Output, RelationalOp};
+
+// A dump is the consolidated contents as a sequence of (value, count) pairs, in the order the
+// output first produced them.
+impl<T: Eq + Hash, Op: RelationalOp<T = T>, S> Output<T, Op, S> {
+    pub fn dump(&mut self) -> Vec<(T, i64)> {
+        let mut counts = IndexMap::new();
+        self.dump_to_index_map(&mut counts);
+        Vec::from_iter(counts)
+    }
+
+    pub fn dump_json(&mut self, writer: impl Write) -> serde_json::Result<()>
+    where
+        T: Serialize,
+    {
+        serde_json::to_writer(writer, &self.dump())
+    }
+
+    pub fn dump_binary(&mut self, mut writer: impl Write) -> Result<(), EncodeError>
+    where
+        T: Serialize,
+    {
+        bincode::serde::encode_into_std_write(
+            self.dump(),
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        Ok(())
+    }
+}
+
+impl<T: Eq + Hash> Input<T> {
+    pub fn seed_json(&self, reader: impl Read) -> serde_json::Result<()>
+    where
+        T: DeserializeOwned,
+    {
+        let values: Vec<(T, i64)> = serde_json::from_reader(reader)?;
+        self.extend_consolidated(values);
+        Ok(())
+    }
+
+    pub fn seed_binary(&self, mut reader: impl Read) -> Result<(), DecodeError>
+    where
+        T: DeserializeOwned,
+    {
+        let values: Vec<(T, i64)> =
+            bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard())?;
+        self.extend_consolidated(values);
+        Ok(())
+    }
+}
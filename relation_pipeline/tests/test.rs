@@ -2,7 +2,7 @@
 
 use std::collections::{HashMap, HashSet};
 
-use relation_pipeline::CreationContext;
+use relation_pipeline::{ContextMismatch, CreationContext, FxBuildHasher, Operand};
 
 #[test]
 fn simple_commit_and_retrieve_test() {
@@ -695,3 +695,303 @@ fn test_maxes_with_high_volume() {
     maxes_relation.dump_to_map(&mut result);
     assert_eq!(result, HashMap::from_iter([((1, 1000), 1)]));
 }
+
+#[test]
+fn test_indexed_output() {
+    let context = CreationContext::new();
+    let (input, relation) = context.new_input::<(i32, i32)>();
+    let mut indexed = context.indexed_output(relation);
+
+    let mut context = context.begin();
+
+    input.update((1, 2), 1);
+    input.update((1, 3), 2);
+    input.update((2, 1), 1);
+    input.update((4, 5), 1);
+
+    context.commit();
+
+    assert_eq!(Vec::from_iter(indexed.get(&1)), vec![(&2, 1), (&3, 2)]);
+    assert!(indexed.contains(&2, &1));
+    assert!(!indexed.contains(&2, &2));
+    assert!(!indexed.contains_key(&3));
+    assert_eq!(
+        Vec::from_iter(indexed.range(2..)),
+        vec![(&2, &1, 1), (&4, &5, 1)]
+    );
+
+    input.update((1, 3), -2);
+    input.update((2, 1), -1);
+
+    context.commit();
+
+    assert_eq!(Vec::from_iter(indexed.get(&1)), vec![(&2, 1)]);
+    assert!(!indexed.contains_key(&2));
+    assert_eq!(indexed.count(&4, &5), 1);
+}
+
+#[test]
+fn test_save_drops_subscribers() {
+    let context = CreationContext::new();
+    let (input, relation) = context.new_input::<i32>();
+    let saved = relation.save();
+    let mut kept = context.output(saved.get_());
+    let dropped = context.output(saved.get_().map(|x| x * 2));
+    let mut undrained = context.output(saved.get_());
+    assert_eq!(saved.subscriber_count(), 3);
+    drop(dropped);
+    assert_eq!(saved.subscriber_count(), 2);
+
+    let mut context = context.begin();
+    input.extend([(1, 1), (2, 1)]);
+    context.commit();
+
+    let mut result = HashMap::new();
+    kept.dump_to_map(&mut result);
+    assert_eq!(result, HashMap::from([(1, 1), (2, 1)]));
+    assert_eq!(saved.backlogs(), vec![0, 2]);
+
+    undrained.for_each(|_, _| {});
+    assert_eq!(saved.backlogs(), vec![0, 0]);
+    drop(undrained);
+    drop(kept);
+    assert_eq!(saved.subscriber_count(), 0);
+    assert!(saved.backlogs().is_empty());
+}
+
+#[test]
+fn test_save_indexed() {
+    let context = CreationContext::new();
+    let (input, relation) = context.new_input::<(i32, i32)>();
+    let saved = relation.save();
+    let mut indexed = saved.indexed();
+    let mut fsts_relation = context.output(saved.get().fsts());
+
+    let mut context = context.begin();
+
+    input.update((1, 2), 1);
+    input.update((3, 4), 1);
+
+    context.commit();
+
+    assert!(indexed.contains(&3, &4));
+    assert_eq!(
+        Vec::from_iter(indexed.iter()),
+        vec![(&1, &2, 1), (&3, &4, 1)]
+    );
+
+    let mut result = HashMap::new();
+    fsts_relation.dump_to_map(&mut result);
+    assert_eq!(result, HashMap::from_iter([(1, 1), (3, 1)]));
+}
+
+#[test]
+fn test_context_mismatch() {
+    let context1 = CreationContext::new();
+    let context2 = CreationContext::new();
+    let (_input1, relation1) = context1.new_input::<(i32, i32)>();
+    let (_input2, relation2) = context2.new_input::<(i32, i32)>();
+    let (_input3, relation3) = context2.new_input::<i32>();
+
+    let Err(err) = relation1.try_join(relation2) else {
+        panic!("join across contexts should fail");
+    };
+    assert_eq!(
+        err,
+        ContextMismatch {
+            operation: "join",
+            operand: Operand::Other,
+            expected: context1.id(),
+            found: context2.id(),
+        }
+    );
+
+    let Err(err) = context1.try_output(relation3) else {
+        panic!("output across contexts should fail");
+    };
+    assert_eq!(err.operand, Operand::Relation);
+    assert_eq!(err.found, context2.id());
+}
+
+#[test]
+fn test_bulk_input() {
+    let context = CreationContext::new();
+    let (input, relation) = context.new_input_with((0..3).map(|x| (x, 1)));
+    let mut counts_relation = context.output(relation.counts());
+
+    let mut context = context.begin();
+
+    input.extend([(1, 1), (3, 1)]);
+    input.extend_consolidated([(4, 1), (2, 1), (4, -1), (2, 1)]);
+
+    context.commit();
+
+    let mut result = HashMap::new();
+    counts_relation.dump_to_map(&mut result);
+    assert_eq!(
+        result,
+        HashMap::from_iter([((0, 1), 1), ((1, 2), 1), ((2, 3), 1), ((3, 1), 1)])
+    );
+
+    input.extend((0..4).map(|x| (x, -1)));
+
+    context.commit();
+
+    counts_relation.dump_to_map(&mut result);
+    assert_eq!(result, HashMap::from_iter([((1, 1), 1), ((2, 2), 1)]));
+}
+
+#[test]
+fn test_sorted_output() {
+    let context = CreationContext::new();
+    let (input, relation) = context.new_input::<(i32, i32)>();
+    let mut sorted_relation = context.output(relation.map(|(k, v)| (v, k)));
+    let (input2, relation2) = context.new_input::<(i32, i32)>();
+    let mut indexed = context.indexed_output(relation2);
+
+    let mut context = context.begin();
+
+    for (k, v) in [(3, 1), (1, 2), (2, 2), (1, 1)] {
+        input.update((k, v), 1);
+        input2.update((k, v), 1);
+        input2.update((k, v + 10), 1);
+    }
+    input.update((1, 1), -1);
+
+    context.commit();
+
+    let mut result = Vec::new();
+    sorted_relation.for_each_sorted(|x, count| result.push((x, count)));
+    assert_eq!(result, vec![((1, 3), 1), ((2, 1), 1), ((2, 2), 1)]);
+
+    assert_eq!(Vec::from_iter(indexed.first_n(&1, 1)), vec![(&1, 1)]);
+    assert_eq!(
+        Vec::from_iter(indexed.last_n(&1, 3)),
+        vec![(&12, 1), (&11, 1), (&2, 1)]
+    );
+}
+
+#[test]
+fn test_custom_hasher() {
+    let context = CreationContext::<FxBuildHasher>::default();
+    let (input1, relation1) = context.new_input::<(i32, i32)>();
+    let (input2, relation2) = context.new_input::<i32>();
+    let relation1 = relation1.save();
+    let mut joined = context.output(relation1.get().join(relation1.get()).distinct());
+    let mut remaining = context.output(relation1.get().antijoin(relation2).top_n(2));
+
+    let mut context = context.begin();
+
+    input1.extend([
+        ((1, 2), 1),
+        ((1, 3), 1),
+        ((2, 4), 1),
+        ((2, 5), 1),
+        ((2, 6), 1),
+    ]);
+    input2.update(1, 1);
+
+    context.commit();
+
+    let mut result = HashMap::new();
+    joined.dump_to_map(&mut result);
+    assert_eq!(result.len(), 13);
+    assert!(result.contains_key(&(1, (2, 3))));
+
+    let mut result = HashMap::with_hasher(FxBuildHasher);
+    remaining.dump_to_map(&mut result);
+    assert_eq!(result, HashMap::from_iter([((2, vec![6, 5]), 1)]));
+}
+
+#[test]
+fn test_random_ns_is_stable() {
+    let context = CreationContext::new();
+    let (input, relation) = context.new_input::<(i32, i32)>();
+    let mut sampled = context.output(relation.random_ns::<3>(42));
+
+    let mut context = context.begin();
+
+    input.extend((0..20).map(|v| ((v % 2, v), 1)));
+
+    context.commit();
+
+    let mut result = Vec::new();
+    sampled.for_each_sorted(|(k, vs), count| result.push((k, vs.to_vec(), count)));
+    assert_eq!(result, vec![(0, vec![16, 4, 0], 1), (1, vec![11, 9, 1], 1)]);
+}
+
+#[test]
+fn test_deterministic_consolidate() {
+    let run = || {
+        let context = CreationContext::new().deterministic();
+        assert!(context.is_deterministic());
+        let (input, relation) = context.new_input::<(i32, i32)>();
+        let saved = relation.save();
+        let mut consolidated = context.output(saved.get().concat(saved.get_()).consolidate());
+
+        let mut context = context.begin();
+
+        input.extend((0..200).map(|x| ((x * 7919 % 101, x), 1)));
+        input.update((5, 1000), 1);
+        input.update((5, 1000), -1);
+        context.commit();
+
+        let mut result = Vec::new();
+        consolidated.for_each(|x, count| result.push((x, count)));
+        result
+    };
+    let result = run();
+    assert_eq!(
+        result,
+        Vec::from_iter((0..200).map(|x| ((x * 7919 % 101, x), 2)))
+    );
+    assert_eq!(run(), result);
+}
+
+#[test]
+#[should_panic(expected = "deterministic mode must be enabled before creating relations")]
+fn test_deterministic_after_relations() {
+    let context = CreationContext::new();
+    let (_input, _relation) = context.new_input::<i32>();
+    let _ = context.deterministic();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_dump_and_seed() {
+    let source = CreationContext::new();
+    let (input, relation) = source.new_input::<(String, i32)>();
+    let mut output = source.output(relation.consolidate());
+    let mut source = source.begin();
+    input.extend([
+        ((String::from("a"), 1), 2),
+        ((String::from("b"), 2), 1),
+        ((String::from("a"), 1), -1),
+        ((String::from("c"), 3), 1),
+        ((String::from("c"), 3), -1),
+    ]);
+    source.commit();
+    let mut json = Vec::new();
+    output.dump_json(&mut json).unwrap();
+
+    let context = CreationContext::new();
+    let (seeded_json, relation_json) = context.new_input::<(String, i32)>();
+    let (seeded_binary, relation_binary) = context.new_input::<(String, i32)>();
+    let mut output_json = context.output(relation_json);
+    let mut output_binary = context.output(relation_binary);
+    let mut context = context.begin();
+    seeded_json.seed_json(json.as_slice()).unwrap();
+    context.commit();
+    let mut binary = Vec::new();
+    output_json.dump_binary(&mut binary).unwrap();
+    seeded_binary.seed_binary(binary.as_slice()).unwrap();
+    context.commit();
+
+    let mut actual = HashMap::new();
+    output_binary.dump_to_map(&mut actual);
+    assert_eq!(
+        actual,
+        HashMap::from([((String::from("a"), 1), 1), ((String::from("b"), 2), 1)])
+    );
+    assert!(seeded_json.seed_json(&br#"[["a", 1]]"#[..]).is_err());
+}
@@ -0,0 +1,88 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    format_rule,
+    parse::{self, ParseError, ParseOptions, Rule},
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cnf {
+    pub num_vars: usize,
+    pub clauses: Vec<Rule>,
+    pub comments: Vec<String>,
+}
+
+pub type Formula = Cnf;
+
+impl Cnf {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_clauses(clauses: impl IntoIterator<Item = Rule>) -> Self {
+        let mut result = Self::new();
+        result.extend(clauses);
+        result
+    }
+
+    pub fn parse(input: impl BufRead) -> Result<Self, ParseError> {
+        parse::try_cnf_with(input, ParseOptions::default())
+    }
+
+    pub fn num_clauses(&self) -> usize {
+        self.clauses.len()
+    }
+
+    // Grows `num_vars` to cover the clause's atoms.
+    pub fn add_clause(&mut self, clause: Rule) {
+        let max_atom = clause.iter().map(|lit| lit.atom().0).max();
+        self.num_vars = self.num_vars.max(max_atom.unwrap_or(0));
+        self.clauses.push(clause);
+    }
+
+    pub fn add_comment(&mut self, comment: impl Into<String>) {
+        self.comments.push(comment.into());
+    }
+
+    pub fn write_dimacs(&self, mut output: impl Write) -> io::Result<()> {
+        write!(output, "{self}")
+    }
+}
+
+impl Extend<Rule> for Cnf {
+    fn extend<I: IntoIterator<Item = Rule>>(&mut self, clauses: I) {
+        for clause in clauses {
+            self.add_clause(clause);
+        }
+    }
+}
+
+impl FromIterator<Rule> for Cnf {
+    fn from_iter<I: IntoIterator<Item = Rule>>(clauses: I) -> Self {
+        Self::from_clauses(clauses)
+    }
+}
+
+// Comments are written ahead of the header, one `c` line each.
+impl Display for Cnf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for comment in &self.comments {
+            for line in comment.split('\n') {
+                if line.is_empty() {
+                    writeln!(f, "c")?;
+                } else {
+                    writeln!(f, "c {line}")?;
+                }
+            }
+        }
+        writeln!(f, "p cnf {} {}", self.num_vars, self.clauses.len())?;
+        for clause in &self.clauses {
+            writeln!(f, "{}", format_rule(clause))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    Literal, format_rule,
+    parse::{self, ParseError, ParseOptions, Rule},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IcnfStep {
+    Clause(Rule),
+    // Solve with all clauses so far, assuming these literals.
+    Query(Vec<Literal>),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Icnf {
+    pub num_vars: usize,
+    pub steps: Vec<IcnfStep>,
+    pub comments: Vec<String>,
+}
+
+impl Icnf {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(input: impl BufRead) -> Result<Self, ParseError> {
+        parse::try_icnf_with(input, ParseOptions::default())
+    }
+
+    pub fn num_queries(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|step| matches!(step, IcnfStep::Query(_)))
+            .count()
+    }
+
+    pub fn add_clause(&mut self, clause: Rule) {
+        self.cover(&clause);
+        self.steps.push(IcnfStep::Clause(clause));
+    }
+
+    pub fn add_query(&mut self, assumptions: Vec<Literal>) {
+        self.cover(&assumptions);
+        self.steps.push(IcnfStep::Query(assumptions));
+    }
+
+    pub fn add_comment(&mut self, comment: impl Into<String>) {
+        self.comments.push(comment.into());
+    }
+
+    pub fn write_dimacs(&self, mut output: impl Write) -> io::Result<()> {
+        write!(output, "{self}")
+    }
+
+    fn cover(&mut self, literals: &[Literal]) {
+        let max_atom = literals.iter().map(|lit| lit.atom().0).max();
+        self.num_vars = self.num_vars.max(max_atom.unwrap_or(0));
+    }
+}
+
+impl Display for Icnf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for comment in &self.comments {
+            for line in comment.split('\n') {
+                if line.is_empty() {
+                    writeln!(f, "c")?;
+                } else {
+                    writeln!(f, "c {line}")?;
+                }
+            }
+        }
+        writeln!(f, "p inccnf")?;
+        for step in &self.steps {
+            match step {
+                IcnfStep::Clause(clause) => writeln!(f, "{}", format_rule(clause))?,
+                IcnfStep::Query(assumptions) => writeln!(f, "a {}", format_rule(assumptions))?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Cursor, Read},
+    path::Path,
+};
+
+use bzip2::bufread::MultiBzDecoder;
+use flate2::bufread::MultiGzDecoder;
+use xz2::bufread::XzDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+const BZIP2_MAGIC: &[u8] = b"BZh";
+
+// Opens `path` for reading, or stdin when it is `-`, decompressing gzip, xz and bzip2 input.
+pub fn open(path: impl AsRef<Path>) -> io::Result<Box<dyn BufRead>> {
+    let path = path.as_ref();
+    if path == Path::new("-") {
+        decompress(io::stdin().lock())
+    } else {
+        decompress(BufReader::new(File::open(path)?))
+    }
+}
+
+// Picks a decoder from the leading magic bytes; anything else is passed through unchanged.
+pub fn decompress(mut input: impl BufRead + 'static) -> io::Result<Box<dyn BufRead>> {
+    // Read the magic bytes out rather than peeking, since pipes may deliver them piecemeal.
+    let mut magic = Vec::new();
+    (&mut input)
+        .take(XZ_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    let compression = Compression::detect(&magic);
+    let input = Cursor::new(magic).chain(input);
+    Ok(match compression {
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(input))),
+        Compression::Xz => Box::new(BufReader::new(XzDecoder::new_multi_decoder(input))),
+        Compression::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(input))),
+        Compression::None => Box::new(input),
+    })
+}
+
+enum Compression {
+    None,
+    Gzip,
+    Xz,
+    Bzip2,
+}
+
+impl Compression {
+    fn detect(magic: &[u8]) -> Self {
+        if magic.starts_with(GZIP_MAGIC) {
+            Self::Gzip
+        } else if magic.starts_with(XZ_MAGIC) {
+            Self::Xz
+        } else if magic.starts_with(BZIP2_MAGIC) {
+            Self::Bzip2
+        } else {
+            Self::None
+        }
+    }
+}
@@ -3,17 +3,32 @@ use std::{
     ops::Not,
 };
 
+pub use self::{
+    cnf::{Cnf, Formula},
+    icnf::{Icnf, IcnfStep},
+    wcnf::Wcnf,
+};
+
+mod cnf;
+mod icnf;
+pub mod input;
 pub mod parse;
+mod wcnf;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct RuleIndex(pub usize);
 
 pub type Level = usize;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Atom(pub usize);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Sign {
     Neg,
     Pos,
@@ -38,6 +53,8 @@ impl Atom {
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Literal(pub isize);
 
 impl Debug for Literal {
@@ -85,6 +102,7 @@ impl Not for Literal {
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LiteralCause {
     DecisionLiteral(Level),
     Propogated(RuleIndex, Level),
@@ -1,36 +1,149 @@
 use std::io::BufRead;
 
-use crate::Literal;
+use crate::{Cnf, Literal};
 
-pub use self::scanner::Scanner;
+pub use self::{
+    error::{ParseError, ParseErrorKind},
+    icnf::try_icnf_with,
+    scanner::Scanner,
+    wcnf::try_wcnf_with,
+};
 
+mod error;
+mod icnf;
 mod scanner;
+mod wcnf;
 
 pub type Rule = Vec<Literal>;
 
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParseOptions {
+    // Ignore anything after the declared number of clauses, and comment lines after the header.
+    pub lenient: bool,
+}
+
+// Lenient, matching what this accepted before parse errors were reported.
+#[track_caller]
 pub fn rules(input: impl BufRead) -> impl Iterator<Item = Rule> {
-    let mut iter = input.lines();
-    let header = loop {
-        let line: String = iter.next().unwrap().unwrap().trim().into();
-        if !(line.is_empty() || line.starts_with('c')) {
-            break line;
+    try_rules_with(input, ParseOptions { lenient: true })
+        .unwrap_or_else(|e| panic!("{e}"))
+        .into_iter()
+}
+
+pub fn try_rules(input: impl BufRead) -> Result<Vec<Rule>, ParseError> {
+    try_rules_with(input, ParseOptions::default())
+}
+
+pub fn try_rules_with(input: impl BufRead, options: ParseOptions) -> Result<Vec<Rule>, ParseError> {
+    Ok(try_cnf_with(input, options)?.clauses)
+}
+
+pub fn try_cnf_with(input: impl BufRead, options: ParseOptions) -> Result<Cnf, ParseError> {
+    use ParseErrorKind::*;
+
+    let mut header = None;
+    let mut comments = Vec::new();
+    let mut rules = Vec::new();
+    let mut clause = Vec::new();
+    let mut end = (1, 1);
+    'lines: for (i, line) in input.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.map_err(|e| ParseError::new(line_no, 1, Io(e)))?;
+        end = (line_no, line.chars().count() + 1);
+        let mut line_tokens = tokens(&line).peekable();
+        let Some(&(column, first)) = line_tokens.peek() else {
+            continue;
+        };
+        if first.starts_with('c') {
+            if header.is_some() && !options.lenient {
+                return Err(ParseError::new(line_no, column, UnexpectedComment));
+            }
+            let comment = &line.trim()[1..];
+            comments.push(comment.strip_prefix(' ').unwrap_or(comment).into());
+            continue;
         }
-    };
-    let mut scanner = Scanner::with_header(header, iter);
-    assert_eq!(scanner.next_token(), Some("p".into()));
-    assert_eq!(scanner.next_token(), Some("cnf".into()));
-    let _nvars = scanner.next_usize();
-    let nclauses = scanner.next_usize();
-    (0..nclauses).map(move |_| {
-        let mut clause = Vec::new();
-        loop {
-            let x = scanner.next_isize();
-            if x == 0 {
-                break;
-            } else {
-                clause.push(Literal(x))
+        let Some((nvars, nclauses)) = header else {
+            if first != "p" {
+                return Err(ParseError::new(line_no, column, MissingHeader));
+            }
+            header = Some(parse_header(line_no, &line)?);
+            continue;
+        };
+        for (column, token) in line_tokens {
+            let error = |kind| ParseError::new(line_no, column, kind);
+            let x: isize = token
+                .parse()
+                .map_err(|_| error(InvalidToken(token.into())))?;
+            if x.unsigned_abs() > nvars {
+                return Err(error(LiteralOutOfRange { literal: x, nvars }));
+            }
+            if x != 0 {
+                clause.push(Literal(x));
+                continue;
+            }
+            if rules.len() == nclauses {
+                return Err(error(ClauseCountMismatch {
+                    declared: nclauses,
+                    actual: nclauses + 1,
+                }));
+            }
+            rules.push(std::mem::take(&mut clause));
+            if rules.len() == nclauses && options.lenient {
+                break 'lines;
             }
         }
-        clause
+    }
+    let (line, column) = end;
+    let Some((nvars, nclauses)) = header else {
+        return Err(ParseError::new(line, column, MissingHeader));
+    };
+    if !clause.is_empty() {
+        return Err(ParseError::new(line, column, UnexpectedEof));
+    }
+    if rules.len() < nclauses {
+        return Err(ParseError::new(
+            line,
+            column,
+            ClauseCountMismatch {
+                declared: nclauses,
+                actual: rules.len(),
+            },
+        ));
+    }
+    Ok(Cnf {
+        num_vars: nvars,
+        clauses: rules,
+        comments,
+    })
+}
+
+fn parse_header(line_no: usize, line: &str) -> Result<(usize, usize), ParseError> {
+    let error = |column| ParseError::new(line_no, column, ParseErrorKind::MalformedHeader);
+    let fields = Vec::from_iter(tokens(line));
+    match fields[..] {
+        [
+            (_, "p"),
+            (_, "cnf"),
+            (nvars_column, nvars),
+            (nclauses_column, nclauses),
+        ] => Ok((
+            nvars.parse().map_err(|_| error(nvars_column))?,
+            nclauses.parse().map_err(|_| error(nclauses_column))?,
+        )),
+        _ => {
+            let end = line.trim_end().chars().count() + 1;
+            Err(error(match fields.get(1) {
+                Some(&(column, token)) if token != "cnf" => column,
+                _ => fields.get(4).map_or(end, |&(column, _)| column),
+            }))
+        }
+    }
+}
+
+// Whitespace-separated tokens with their 1-based character column.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace().map(move |token| {
+        let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+        (line[..offset].chars().count() + 1, token)
     })
 }
@@ -0,0 +1,71 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    Io(io::Error),
+    MissingHeader,
+    MalformedHeader,
+    InvalidToken(String),
+    UnexpectedEof,
+    LiteralOutOfRange { literal: isize, nvars: usize },
+    ClauseCountMismatch { declared: usize, actual: usize },
+    UnexpectedComment,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, column: usize, kind: ParseErrorKind) -> Self {
+        Self { line, column, kind }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )
+    }
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "read error: {e}"),
+            Self::MissingHeader => write!(f, "expected `p cnf <variables> <clauses>` header"),
+            Self::MalformedHeader => write!(f, "malformed `p cnf` header"),
+            Self::InvalidToken(token) => write!(f, "invalid token `{token}`"),
+            Self::UnexpectedEof => write!(f, "unexpected end of input"),
+            Self::LiteralOutOfRange { literal, nvars } => {
+                write!(
+                    f,
+                    "literal {literal} exceeds declared variable count {nvars}"
+                )
+            }
+            Self::ClauseCountMismatch { declared, actual } => {
+                write!(f, "header declares {declared} clauses but found {actual}")
+            }
+            Self::UnexpectedComment => write!(f, "comment after header"),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::{io::BufRead, mem};
+
+use crate::{Icnf, Literal};
+
+use super::{ParseError, ParseErrorKind, ParseOptions, tokens};
+
+// `p inccnf` declares no counts; clauses and `a <literals> 0` queries follow in any order.
+pub fn try_icnf_with(input: impl BufRead, options: ParseOptions) -> Result<Icnf, ParseError> {
+    use ParseErrorKind::*;
+
+    let mut header = false;
+    let mut icnf = Icnf::new();
+    let mut assumption = false;
+    let mut clause = Vec::new();
+    let mut end = (1, 1);
+    for (i, line) in input.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.map_err(|e| ParseError::new(line_no, 1, Io(e)))?;
+        end = (line_no, line.chars().count() + 1);
+        let mut line_tokens = tokens(&line).peekable();
+        let Some(&(column, first)) = line_tokens.peek() else {
+            continue;
+        };
+        if first.starts_with('c') {
+            if header && !options.lenient {
+                return Err(ParseError::new(line_no, column, UnexpectedComment));
+            }
+            let comment = &line.trim()[1..];
+            icnf.comments
+                .push(comment.strip_prefix(' ').unwrap_or(comment).into());
+            continue;
+        }
+        if !header {
+            if first != "p" {
+                return Err(ParseError::new(line_no, column, MissingHeader));
+            }
+            match Vec::from_iter(tokens(&line))[..] {
+                [(_, "p"), (_, "inccnf")] => {}
+                [_, (column, _), ..] => {
+                    return Err(ParseError::new(line_no, column, MalformedHeader));
+                }
+                _ => {
+                    let end = line.trim_end().chars().count() + 1;
+                    return Err(ParseError::new(line_no, end, MalformedHeader));
+                }
+            }
+            header = true;
+            continue;
+        }
+        for (column, token) in line_tokens {
+            if token == "a" && clause.is_empty() && !assumption {
+                assumption = true;
+                continue;
+            }
+            let x: isize = token
+                .parse()
+                .map_err(|_| ParseError::new(line_no, column, InvalidToken(token.into())))?;
+            if x != 0 {
+                clause.push(Literal(x));
+                continue;
+            }
+            let clause = mem::take(&mut clause);
+            if mem::take(&mut assumption) {
+                icnf.add_query(clause);
+            } else {
+                icnf.add_clause(clause);
+            }
+        }
+    }
+    let (line, column) = end;
+    if !header {
+        return Err(ParseError::new(line, column, MissingHeader));
+    }
+    if assumption || !clause.is_empty() {
+        return Err(ParseError::new(line, column, UnexpectedEof));
+    }
+    Ok(icnf)
+}
@@ -1,46 +1,94 @@
-use std::fmt::Debug;
+use std::io;
+
+use super::{ParseError, ParseErrorKind, tokens};
 
 pub struct Scanner<L: Iterator> {
     line_scanner: L,
-    next_tokens: Vec<String>,
+    next_tokens: Vec<(usize, String)>,
+    line: usize,
+    column: usize,
 }
 
-fn split_tokens(line: String) -> Vec<String> {
-    let mut v: Vec<String> = line.split_whitespace().map(String::from).collect();
+fn split_tokens(line: &str) -> Vec<(usize, String)> {
+    let mut v = Vec::from_iter(tokens(line).map(|(column, token)| (column, token.into())));
     v.reverse();
     v
 }
 
-impl<Err: Debug, L: Iterator<Item = Result<String, Err>>> Scanner<L> {
-    pub fn new(mut line_scanner: L) -> Self {
-        let next_line = line_scanner.next().unwrap().unwrap();
-        Self::with_header(next_line, line_scanner)
+impl<Err: Into<io::Error>, L: Iterator<Item = Result<String, Err>>> Scanner<L> {
+    pub fn new(line_scanner: L) -> Self {
+        Scanner {
+            line_scanner,
+            next_tokens: Vec::new(),
+            line: 0,
+            column: 1,
+        }
     }
 
     pub fn with_header(next_line: String, line_scanner: L) -> Self {
         Scanner {
             line_scanner,
-            next_tokens: split_tokens(next_line),
+            next_tokens: split_tokens(&next_line),
+            line: 1,
+            column: 1,
         }
     }
 
+    // Line and column of the most recently returned token.
+    pub fn position(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    #[track_caller]
     pub fn next_token(&mut self) -> Option<String> {
+        self.try_next_token().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_next_token(&mut self) -> Result<Option<String>, ParseError> {
         loop {
-            if let Some(token) = self.next_tokens.pop() {
-                return Some(token);
+            if let Some((column, token)) = self.next_tokens.pop() {
+                self.column = column;
+                return Ok(Some(token));
             }
-            let next_line = self.line_scanner.next()?.unwrap();
-            self.next_tokens = split_tokens(next_line);
+            let Some(next_line) = self.line_scanner.next() else {
+                return Ok(None);
+            };
+            self.line += 1;
+            self.column = 1;
+            let next_line = next_line
+                .map_err(|e| ParseError::new(self.line, 1, ParseErrorKind::Io(e.into())))?;
+            self.next_tokens = split_tokens(&next_line);
         }
     }
 
     #[track_caller]
     pub fn next_usize(&mut self) -> usize {
-        self.next_token().unwrap().parse::<usize>().unwrap()
+        self.try_next_usize().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_next_usize(&mut self) -> Result<usize, ParseError> {
+        self.try_next_parsed()
     }
 
     #[track_caller]
     pub fn next_isize(&mut self) -> isize {
-        self.next_token().unwrap().parse::<isize>().unwrap()
+        self.try_next_isize().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_next_isize(&mut self) -> Result<isize, ParseError> {
+        self.try_next_parsed()
+    }
+
+    fn try_next_parsed<T: std::str::FromStr>(&mut self) -> Result<T, ParseError> {
+        let Some(token) = self.try_next_token()? else {
+            return Err(ParseError::new(
+                self.line,
+                self.column,
+                ParseErrorKind::UnexpectedEof,
+            ));
+        };
+        token.parse().map_err(|_| {
+            ParseError::new(self.line, self.column, ParseErrorKind::InvalidToken(token))
+        })
     }
 }
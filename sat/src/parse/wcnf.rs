@@ -0,0 +1,149 @@
+use std::{io::BufRead, mem};
+
+use crate::{Literal, Wcnf};
+
+use super::{ParseError, ParseErrorKind, ParseOptions, tokens};
+
+struct Header {
+    nvars: usize,
+    nclauses: usize,
+    top: Option<u64>,
+}
+
+enum Weight {
+    Hard,
+    Soft(u64),
+}
+
+// Old-style files start with `p wcnf <variables> <clauses> [<top>]` and weigh hard clauses with
+// `top`; new-style files have no header and mark hard clauses with `h`.
+pub fn try_wcnf_with(input: impl BufRead, options: ParseOptions) -> Result<Wcnf, ParseError> {
+    use ParseErrorKind::*;
+
+    let mut header = None;
+    let mut new_style = false;
+    let mut wcnf = Wcnf::new();
+    let mut weight = None;
+    let mut clause = Vec::new();
+    let mut end = (1, 1);
+    'lines: for (i, line) in input.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.map_err(|e| ParseError::new(line_no, 1, Io(e)))?;
+        end = (line_no, line.chars().count() + 1);
+        let mut line_tokens = tokens(&line).peekable();
+        let Some(&(column, first)) = line_tokens.peek() else {
+            continue;
+        };
+        if first.starts_with('c') {
+            if header.is_some() && !options.lenient {
+                return Err(ParseError::new(line_no, column, UnexpectedComment));
+            }
+            let comment = &line.trim()[1..];
+            wcnf.comments
+                .push(comment.strip_prefix(' ').unwrap_or(comment).into());
+            continue;
+        }
+        if header.is_none() && !new_style {
+            if first == "p" {
+                header = Some(parse_header(line_no, &line)?);
+                continue;
+            }
+            new_style = true;
+        }
+        for (column, token) in line_tokens {
+            let error = |kind| ParseError::new(line_no, column, kind);
+            let invalid = || error(InvalidToken(token.into()));
+            let Some(current_weight) = &weight else {
+                weight = Some(match (&header, token) {
+                    (None, "h") => Weight::Hard,
+                    (header, _) => {
+                        let w: u64 = token.parse().map_err(|_| invalid())?;
+                        if w == 0 {
+                            return Err(invalid());
+                        }
+                        match header {
+                            Some(Header { top: Some(top), .. }) if w >= *top => Weight::Hard,
+                            _ => Weight::Soft(w),
+                        }
+                    }
+                });
+                continue;
+            };
+            let x: isize = token.parse().map_err(|_| invalid())?;
+            if let Some(&Header { nvars, .. }) = header.as_ref()
+                && x.unsigned_abs() > nvars
+            {
+                return Err(error(LiteralOutOfRange { literal: x, nvars }));
+            }
+            if x != 0 {
+                clause.push(Literal(x));
+                continue;
+            }
+            let count = wcnf.hard.len() + wcnf.soft.len();
+            if let Some(&Header { nclauses, .. }) = header.as_ref()
+                && count == nclauses
+            {
+                return Err(error(ClauseCountMismatch {
+                    declared: nclauses,
+                    actual: nclauses + 1,
+                }));
+            }
+            let clause = mem::take(&mut clause);
+            match current_weight {
+                Weight::Hard => wcnf.add_hard(clause),
+                &Weight::Soft(w) => wcnf.add_soft(w, clause),
+            }
+            weight = None;
+            if header.as_ref().is_some_and(|h| count + 1 == h.nclauses) && options.lenient {
+                break 'lines;
+            }
+        }
+    }
+    let (line, column) = end;
+    if weight.is_some() {
+        return Err(ParseError::new(line, column, UnexpectedEof));
+    }
+    if let Some(Header {
+        nvars, nclauses, ..
+    }) = header
+    {
+        let count = wcnf.hard.len() + wcnf.soft.len();
+        if count < nclauses {
+            return Err(ParseError::new(
+                line,
+                column,
+                ClauseCountMismatch {
+                    declared: nclauses,
+                    actual: count,
+                },
+            ));
+        }
+        wcnf.num_vars = nvars;
+    }
+    Ok(wcnf)
+}
+
+fn parse_header(line_no: usize, line: &str) -> Result<Header, ParseError> {
+    let error = |column| ParseError::new(line_no, column, ParseErrorKind::MalformedHeader);
+    let fields = Vec::from_iter(tokens(line));
+    let parse = |(column, token): (usize, &str)| token.parse().map_err(|_| error(column));
+    match fields[..] {
+        [(_, "p"), (_, "wcnf"), nvars, nclauses] => Ok(Header {
+            nvars: parse(nvars)? as usize,
+            nclauses: parse(nclauses)? as usize,
+            top: None,
+        }),
+        [(_, "p"), (_, "wcnf"), nvars, nclauses, top] => Ok(Header {
+            nvars: parse(nvars)? as usize,
+            nclauses: parse(nclauses)? as usize,
+            top: Some(parse(top)?),
+        }),
+        _ => {
+            let end = line.trim_end().chars().count() + 1;
+            Err(error(match fields.get(1) {
+                Some(&(column, token)) if token != "wcnf" => column,
+                _ => fields.get(5).map_or(end, |&(column, _)| column),
+            }))
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    Literal, format_rule,
+    parse::{self, ParseError, ParseOptions, Rule},
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wcnf {
+    pub num_vars: usize,
+    pub hard: Vec<Rule>,
+    pub soft: Vec<(u64, Rule)>,
+    pub comments: Vec<String>,
+}
+
+impl Wcnf {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(input: impl BufRead) -> Result<Self, ParseError> {
+        parse::try_wcnf_with(input, ParseOptions::default())
+    }
+
+    pub fn add_hard(&mut self, clause: Rule) {
+        self.cover(&clause);
+        self.hard.push(clause);
+    }
+
+    pub fn add_soft(&mut self, weight: u64, clause: Rule) {
+        self.cover(&clause);
+        self.soft.push((weight, clause));
+    }
+
+    pub fn add_comment(&mut self, comment: impl Into<String>) {
+        self.comments.push(comment.into());
+    }
+
+    // Total weight of the soft clauses `model` leaves unsatisfied, reading atoms it omits as false.
+    pub fn cost(&self, model: &[Literal]) -> u64 {
+        let model = HashSet::<&Literal>::from_iter(model);
+        let holds = |lit: &Literal| model.contains(lit) || (lit.0 < 0 && !model.contains(&!*lit));
+        self.soft
+            .iter()
+            .filter(|(_, clause)| !clause.iter().any(holds))
+            .map(|&(weight, _)| weight)
+            .fold(0, u64::saturating_add)
+    }
+
+    // Writes the header-less format, with `h` marking hard clauses.
+    pub fn write_dimacs(&self, mut output: impl Write) -> io::Result<()> {
+        write!(output, "{self}")
+    }
+
+    fn cover(&mut self, clause: &Rule) {
+        let max_atom = clause.iter().map(|lit| lit.atom().0).max();
+        self.num_vars = self.num_vars.max(max_atom.unwrap_or(0));
+    }
+}
+
+impl Display for Wcnf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for comment in &self.comments {
+            for line in comment.split('\n') {
+                if line.is_empty() {
+                    writeln!(f, "c")?;
+                } else {
+                    writeln!(f, "c {line}")?;
+                }
+            }
+        }
+        for clause in &self.hard {
+            writeln!(f, "h {}", format_rule(clause))?;
+        }
+        for (weight, clause) in &self.soft {
+            writeln!(f, "{weight} {}", format_rule(clause))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,279 @@
+use std::io::{BufReader, Read, Write};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use sat::{
+    Cnf, Icnf, IcnfStep, Literal, Wcnf, input,
+    parse::{self, ParseErrorKind, ParseOptions},
+};
+
+fn parse_error(input: &str, lenient: bool) -> (usize, usize, ParseErrorKind) {
+    let error = parse::try_rules_with(input.as_bytes(), ParseOptions { lenient }).unwrap_err();
+    (error.line, error.column, error.kind)
+}
+
+#[test]
+fn test_parse_rules() {
+    let input = "c example\n\np cnf 3 3\n1 -2 0\n3\n  -1 0 0\n";
+    let rules = parse::try_rules(input.as_bytes()).unwrap();
+    assert_eq!(
+        rules,
+        vec![
+            vec![Literal(1), Literal(-2)],
+            vec![Literal(3), Literal(-1)],
+            vec![]
+        ]
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    use ParseErrorKind::*;
+
+    assert!(matches!(
+        parse_error("1 2 0\n", false),
+        (1, 1, MissingHeader)
+    ));
+    assert!(matches!(
+        parse_error("c only\n", false),
+        (1, 7, MissingHeader)
+    ));
+    assert!(matches!(
+        parse_error("p cnf 2\n", false),
+        (1, 8, MalformedHeader)
+    ));
+    assert!(matches!(
+        parse_error("p dnf 2 1\n", false),
+        (1, 3, MalformedHeader)
+    ));
+    assert!(matches!(
+        parse_error("p cnf 2 x\n", false),
+        (1, 9, MalformedHeader)
+    ));
+    assert!(matches!(
+        parse_error("p cnf 2 1\n1 -x 0\n", false),
+        (2, 3, InvalidToken(token)) if token == "-x"
+    ));
+    assert!(matches!(
+        parse_error("p cnf 2 1\n1 -3 0\n", false),
+        (
+            2,
+            3,
+            LiteralOutOfRange {
+                literal: -3,
+                nvars: 2
+            }
+        )
+    ));
+    assert!(matches!(
+        parse_error("p cnf 2 1\n1 2\n", false),
+        (2, 4, UnexpectedEof)
+    ));
+    assert!(matches!(
+        parse_error("p cnf 2 2\n1 2 0\n", false),
+        (
+            2,
+            6,
+            ClauseCountMismatch {
+                declared: 2,
+                actual: 1
+            }
+        )
+    ));
+    assert!(matches!(
+        parse_error("p cnf 2 1\n1 0 2 0\n", false),
+        (
+            2,
+            7,
+            ClauseCountMismatch {
+                declared: 1,
+                actual: 2
+            }
+        )
+    ));
+    assert!(matches!(
+        parse_error("p cnf 2 1\nc mid\n1 0\n", false),
+        (2, 1, UnexpectedComment)
+    ));
+}
+
+#[test]
+fn test_parse_lenient() {
+    let input = "p cnf 2 2\nc mid\n1 0 2\nc split\n0 -1 -2 0\n%\n0\n";
+    let rules = parse::try_rules_with(input.as_bytes(), ParseOptions { lenient: true }).unwrap();
+    assert_eq!(rules, vec![vec![Literal(1)], vec![Literal(2)]]);
+    assert!(matches!(
+        parse_error("p cnf 2 1\n1 3 0\n", true),
+        (2, 3, ParseErrorKind::LiteralOutOfRange { .. })
+    ));
+    assert!(matches!(
+        parse_error("p cnf 2 2\n1 0\n", true),
+        (
+            2,
+            4,
+            ParseErrorKind::ClauseCountMismatch {
+                declared: 2,
+                actual: 1
+            }
+        )
+    ));
+}
+
+#[test]
+fn test_decompress() {
+    let cnf = b"p cnf 2 2\n1 -2 0\n2 0\n";
+    let expected = parse::try_rules(&cnf[..]).unwrap();
+
+    let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gzip.write_all(cnf).unwrap();
+    let mut xz = xz2::write::XzEncoder::new(Vec::new(), 6);
+    xz.write_all(cnf).unwrap();
+    let mut bzip2 = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+    bzip2.write_all(cnf).unwrap();
+
+    for compressed in [
+        cnf.to_vec(),
+        gzip.finish().unwrap(),
+        xz.finish().unwrap(),
+        bzip2.finish().unwrap(),
+    ] {
+        // A one-byte buffer hands the magic bytes over piecemeal, as a pipe may.
+        let reader = BufReader::with_capacity(1, std::io::Cursor::new(compressed));
+        let mut decompressed = Vec::new();
+        input::decompress(reader)
+            .unwrap()
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, cnf);
+        assert_eq!(parse::try_rules(&decompressed[..]).unwrap(), expected);
+    }
+}
+
+#[test]
+fn test_cnf_roundtrip() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(1414);
+
+    for _ in 0..50 {
+        let mut cnf = Cnf::new();
+        for _ in 0..rng.random_range(0..3) {
+            cnf.add_comment(format!("comment {}", rng.random::<u32>()));
+        }
+        cnf.add_comment("");
+        for _ in 0..rng.random_range(0..20) {
+            let len = rng.random_range(0..5);
+            cnf.add_clause(Vec::from_iter((0..len).map(|_| {
+                let atom = rng.random_range(1..30i32) as isize;
+                Literal(if rng.random_bool(0.5) { atom } else { -atom })
+            })));
+        }
+        cnf.num_vars += rng.random_range(0..3);
+
+        let mut dimacs = Vec::new();
+        cnf.write_dimacs(&mut dimacs).unwrap();
+        assert_eq!(String::from_utf8(dimacs.clone()).unwrap(), cnf.to_string());
+        assert_eq!(Cnf::parse(&dimacs[..]).unwrap(), cnf);
+        assert!(parse::rules(&dimacs[..]).eq(cnf.clauses.iter().cloned()));
+    }
+
+    let cnf = Cnf::from_iter([vec![Literal(3), Literal(-1)], vec![]]);
+    assert_eq!(cnf.to_string(), "p cnf 3 2\n3 -1 0\n0\n");
+}
+
+#[test]
+fn test_parse_wcnf() {
+    let old_style = "c old\np wcnf 3 4 10\n10 1 2 0\n3 -1 0\n1 -2\n 3 0\n12 -3 0\n";
+    let wcnf = parse::try_wcnf_with(old_style.as_bytes(), ParseOptions::default()).unwrap();
+    assert_eq!(wcnf.num_vars, 3);
+    assert_eq!(
+        wcnf.hard,
+        vec![vec![Literal(1), Literal(2)], vec![Literal(-3)]]
+    );
+    assert_eq!(
+        wcnf.soft,
+        vec![(3, vec![Literal(-1)]), (1, vec![Literal(-2), Literal(3)])]
+    );
+    assert_eq!(wcnf.comments, vec![String::from("old")]);
+
+    let no_top = parse::try_wcnf_with("p wcnf 1 1\n7 1 0\n".as_bytes(), ParseOptions::default());
+    assert_eq!(no_top.unwrap().soft, vec![(7, vec![Literal(1)])]);
+
+    let new_style = "c new\nh 1 2 0\n3 -1 0\nc anywhere\n1 -2 3 0\nh -3 0\n";
+    let parsed = parse::try_wcnf_with(new_style.as_bytes(), ParseOptions::default()).unwrap();
+    assert_eq!(parsed.hard, wcnf.hard);
+    assert_eq!(parsed.soft, wcnf.soft);
+    assert_eq!(Wcnf::parse(parsed.to_string().as_bytes()).unwrap(), parsed);
+    assert_eq!(parsed.cost(&[Literal(1), Literal(-3)]), 3);
+    assert_eq!(parsed.cost(&[Literal(2)]), 1);
+
+    let error = |input: &str| {
+        let error = parse::try_wcnf_with(input.as_bytes(), ParseOptions::default()).unwrap_err();
+        (error.line, error.column, error.kind)
+    };
+    assert!(matches!(
+        error("p wcnf 2\n"),
+        (1, 9, ParseErrorKind::MalformedHeader)
+    ));
+    assert!(matches!(
+        error("p wcnf 2 1 5\nh 1 0\n"),
+        (2, 1, ParseErrorKind::InvalidToken(token)) if token == "h"
+    ));
+    assert!(matches!(
+        error("0 1 0\n"),
+        (1, 1, ParseErrorKind::InvalidToken(token)) if token == "0"
+    ));
+    assert!(matches!(
+        error("h 1 2\n"),
+        (1, 6, ParseErrorKind::UnexpectedEof)
+    ));
+    assert!(matches!(
+        error("p wcnf 2 1 5\n5 3 0\n"),
+        (
+            2,
+            3,
+            ParseErrorKind::LiteralOutOfRange {
+                literal: 3,
+                nvars: 2
+            }
+        )
+    ));
+}
+
+#[test]
+fn test_parse_icnf() {
+    let input = "c inc\np inccnf\n1 2 0\na -1 0\n-2\n3 0 a 0\na -3 2\n0\n";
+    let icnf = parse::try_icnf_with(input.as_bytes(), ParseOptions::default()).unwrap();
+    assert_eq!(icnf.num_vars, 3);
+    assert_eq!(
+        icnf.steps,
+        vec![
+            IcnfStep::Clause(vec![Literal(1), Literal(2)]),
+            IcnfStep::Query(vec![Literal(-1)]),
+            IcnfStep::Clause(vec![Literal(-2), Literal(3)]),
+            IcnfStep::Query(vec![]),
+            IcnfStep::Query(vec![Literal(-3), Literal(2)]),
+        ]
+    );
+    assert_eq!(icnf.num_queries(), 3);
+    assert_eq!(Icnf::parse(icnf.to_string().as_bytes()).unwrap(), icnf);
+
+    let error = |input: &str| {
+        let error = parse::try_icnf_with(input.as_bytes(), ParseOptions::default()).unwrap_err();
+        (error.line, error.column, error.kind)
+    };
+    assert!(matches!(
+        error("p cnf 1 1\n"),
+        (1, 3, ParseErrorKind::MalformedHeader)
+    ));
+    assert!(matches!(
+        error("a 1 0\n"),
+        (1, 1, ParseErrorKind::MissingHeader)
+    ));
+    assert!(matches!(
+        error("p inccnf\n1 a 0\n"),
+        (2, 3, ParseErrorKind::InvalidToken(token)) if token == "a"
+    ));
+    assert!(matches!(
+        error("p inccnf\na 1\n"),
+        (2, 4, ParseErrorKind::UnexpectedEof)
+    ));
+}
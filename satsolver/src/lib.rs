@@ -2,12 +2,44 @@ use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::io::Write;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-use loopy_relations::{CreationContext, ExecutionContext};
+use loopy_relations::{CreationContext, ExecutionContext, FxBuildHasher};
 use once_cell::unsync::Lazy;
-use sat::{Atom, Literal, RuleIndex, Sign, sanitize_rule};
+use sat::{Atom, Level, Literal, RuleIndex, Sign, sanitize_rule};
 use satsolver_relgraph::{RelGraph, signal};
 
+pub mod maxsat;
+pub mod unsat_core;
+
+#[derive(Clone, Default)]
+pub struct Options {
+    pub deterministic: bool,
+}
+
+// Budgets for each call to `solve_with_assumptions`; running out of any gives `Unknown`.
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    pub time: Option<Duration>,
+    pub conflicts: Option<u64>,
+    pub decisions: Option<u64>,
+}
+
+// Totals over every call to `solve_with_assumptions` on a solver.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub decisions: u64,
+    pub conflicts: u64,
+    pub learnt_rules: u64,
+    pub backjumps: u64,
+    pub invalidated_selections: u64,
+    pub discovered_singletons: u64,
+    pub discovered_binaries: u64,
+    pub discovered_equivalences: u64,
+}
+
 pub struct Solver {
     context: ExecutionContext,
     next_rule_index: usize,
@@ -16,73 +48,205 @@ pub struct Solver {
     required_atoms: HashSet<Atom>,
     equivalence_graph: HashMap<Literal, Literal>,
     learnt_rules: HashSet<Vec<Literal>>,
+    unsat: bool,
+    limits: Limits,
+    terminate: Arc<AtomicBool>,
+    stats: Stats,
+    #[allow(clippy::type_complexity)]
+    progress: Option<(Duration, Box<dyn FnMut(&Stats)>)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveResult {
+    Sat(Vec<Literal>),
+    // The assumptions responsible, empty if the rules are unsatisfiable on their own.
+    Unsat(Vec<Literal>),
+    // A limit ran out or termination was requested before an answer was found.
+    Unknown,
 }
 
 impl Solver {
     pub fn new(
         rules: impl IntoIterator<Item = Vec<Literal>>,
-        mut proof_output: Box<dyn Write>,
+        proof_output: Box<dyn Write>,
     ) -> Option<Self> {
-        let mut context = CreationContext::new();
+        Self::with_options(rules, proof_output, Options::default())
+    }
+
+    pub fn with_options(
+        rules: impl IntoIterator<Item = Vec<Literal>>,
+        proof_output: Box<dyn Write>,
+        options: Options,
+    ) -> Option<Self> {
+        let mut context = CreationContext::<FxBuildHasher>::default();
+        if options.deterministic {
+            context = context.deterministic();
+        }
         let relgraph = RelGraph::construct(&mut context);
-        let mut next_rule_index = 0;
-        let mut required_atoms = HashSet::new();
+        let mut solver = Self {
+            context: context.begin(),
+            next_rule_index: 0,
+            relgraph,
+            proof_output,
+            required_atoms: HashSet::new(),
+            equivalence_graph: HashMap::new(),
+            learnt_rules: HashSet::new(),
+            unsat: false,
+            limits: Limits::default(),
+            terminate: Arc::default(),
+            stats: Stats::default(),
+            progress: None,
+        };
+        solver.add_rules(rules);
+        (!solver.unsat).then_some(solver)
+    }
+
+    // Adds input rules between calls to `solve_with_assumptions`; they are not written to the proof.
+    pub fn add_rules(&mut self, rules: impl IntoIterator<Item = Vec<Literal>>) {
+        let mut new_rules = Vec::new();
         for rule in rules {
             let rule = match sanitize_rule(rule) {
                 Ok(rule) => rule,
                 Err(req_atom) => {
-                    required_atoms.insert(req_atom);
+                    self.required_atoms.insert(req_atom);
                     continue;
                 }
             };
             if rule.is_empty() {
-                writeln!(proof_output, "0").unwrap();
-                return None;
+                writeln!(self.proof_output, "0").unwrap();
+                self.unsat = true;
+                return;
             }
-            relgraph.add_rule(RuleIndex(next_rule_index), &rule);
-            next_rule_index += 1;
+            new_rules.push((RuleIndex(self.next_rule_index), rule));
+            self.next_rule_index += 1;
         }
-        Some(Self {
-            context: context.begin(),
-            next_rule_index,
-            relgraph,
-            proof_output,
-            required_atoms,
-            equivalence_graph: HashMap::new(),
-            learnt_rules: HashSet::new(),
-        })
+        self.relgraph.add_rules(
+            new_rules
+                .iter()
+                .map(|(rule_index, rule)| (*rule_index, rule.as_slice())),
+        );
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    // Setting the flag, e.g. from a signal handler, stops the current and any later search with
+    // `Unknown` until it is cleared again.
+    pub fn terminate_flag(&self) -> Arc<AtomicBool> {
+        self.terminate.clone()
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    // `report` is called with the current totals about every `interval` while searching.
+    pub fn set_progress(&mut self, interval: Duration, report: impl FnMut(&Stats) + 'static) {
+        self.progress = Some((interval, Box::new(report)));
     }
 
-    pub fn solve(mut self) -> Option<Vec<Literal>> {
+    pub fn solve(mut self) -> SolveResult {
+        self.solve_with_assumptions(&[])
+    }
+
+    // Assumptions are selected together as level 1 and retracted before returning, while learnt
+    // rules are kept for later calls.
+    pub fn solve_with_assumptions(&mut self, assumptions: &[Literal]) -> SolveResult {
+        if self.unsat {
+            return SolveResult::Unsat(Vec::new());
+        }
         let mut level = 0;
+        let result = self.search(assumptions, &mut level);
+        for _ in 0..level {
+            self.context.pop_frame_();
+        }
+        if result == SolveResult::Unsat(Vec::new()) {
+            self.unsat = true;
+        }
+        result
+    }
+
+    fn search(&mut self, assumptions: &[Literal], level: &mut Level) -> SolveResult {
+        let base_level = usize::from(!assumptions.is_empty());
         let mut selected_literals: HashMap<Atom, Sign> = HashMap::new();
         let mut literal_at_level: Vec<Literal> = Vec::new();
+        let start = Instant::now();
+        let initial = self.stats;
+        let mut last_report = start;
         loop {
+            let conflicts = self.stats.conflicts - initial.conflicts;
+            let decisions = self.stats.decisions - initial.decisions;
+            if self.terminate.load(Ordering::Relaxed)
+                || self.limits.time.is_some_and(|time| start.elapsed() >= time)
+                || self.limits.conflicts.is_some_and(|max| conflicts >= max)
+                || self.limits.decisions.is_some_and(|max| decisions >= max)
+            {
+                return SolveResult::Unknown;
+            }
+            if let Some((interval, report)) = &mut self.progress
+                && last_report.elapsed() >= *interval
+            {
+                report(&self.stats);
+                last_report = Instant::now();
+            }
             match self.context.commit() {
                 Some(signal::ASSIGNMENT_CONFLICT | signal::VIOLATED_RULE) => {
+                    self.stats.conflicts += 1;
                     let (new_rule, new_rule_level) = self.relgraph.derive_conflict_rule();
+                    if new_rule_level == base_level && base_level > 0 {
+                        for _ in 0..*level {
+                            self.context.pop_frame_();
+                        }
+                        *level = 0;
+                        selected_literals.clear();
+                        literal_at_level.clear();
+                        // Two assumptions, or an assumption and a root assignment, clash through
+                        // an equivalence found since they were selected; reselecting reports it.
+                        if new_rule.iter().any(|&x| new_rule.contains(&!x)) {
+                            continue;
+                        }
+                        let representatives = path_compress(&self.equivalence_graph);
+                        let failed = Vec::from_iter(assumptions.iter().copied().filter(|x| {
+                            new_rule.contains(&!representatives.get(x).copied().unwrap_or(*x))
+                        }));
+                        if !self.learnt_rules.contains(&new_rule) {
+                            self.add_rule(new_rule);
+                            self.stats.learnt_rules += 1;
+                        }
+                        return SolveResult::Unsat(failed);
+                    }
                     self.add_rule(new_rule);
-                    while level >= new_rule_level {
-                        if level == 0 {
-                            return None;
+                    self.stats.learnt_rules += 1;
+                    if *level >= new_rule_level {
+                        self.stats.backjumps += 1;
+                    }
+                    while *level >= new_rule_level {
+                        if *level == 0 {
+                            return SolveResult::Unsat(Vec::new());
                         }
                         self.context.pop_frame_();
-                        let lit = literal_at_level.pop().unwrap();
-                        let removed = selected_literals.remove(&lit.atom());
-                        assert_eq!(removed, Some(lit.sign()));
-                        level -= 1;
+                        if *level > base_level {
+                            let lit = literal_at_level.pop().unwrap();
+                            let removed = selected_literals.remove(&lit.atom());
+                            assert_eq!(removed, Some(lit.sign()));
+                        }
+                        *level -= 1;
                     }
                 }
                 Some(signal::SELECTION_INVALIDATED) => {
+                    self.stats.invalidated_selections += 1;
                     self.context.pop_frame_();
-                    let lit = literal_at_level.pop().unwrap();
-                    let removed = selected_literals.remove(&lit.atom());
-                    assert_eq!(removed, Some(lit.sign()));
-                    level -= 1;
+                    if *level > base_level {
+                        let lit = literal_at_level.pop().unwrap();
+                        let removed = selected_literals.remove(&lit.atom());
+                        assert_eq!(removed, Some(lit.sign()));
+                    }
+                    *level -= 1;
                 }
                 Some(signal::ROOT_CONFLICT) => {
                     writeln!(self.proof_output, "0").unwrap();
-                    return None;
+                    return SolveResult::Unsat(Vec::new());
                 }
                 Some(
                     code @ (signal::SINGLETON_DISCOVERED_1
@@ -90,6 +254,11 @@ impl Solver {
                     | signal::BINARY_DISCOVERED),
                 ) => {
                     let discovered_rule = self.relgraph.get_discovered_rule(code);
+                    if code == signal::BINARY_DISCOVERED {
+                        self.stats.discovered_binaries += 1;
+                    } else {
+                        self.stats.discovered_singletons += 1;
+                    }
                     self.add_rule(discovered_rule);
                 }
                 Some(signal::EQUIVALENCE_DISCOVERED) => {
@@ -98,31 +267,61 @@ impl Solver {
                         assert!(lit.0 < 0);
                         writeln!(self.proof_output, "{} 0", atom.0).unwrap();
                         writeln!(self.proof_output, "0").unwrap();
-                        return None;
+                        return SolveResult::Unsat(Vec::new());
                     }
+                    self.stats.discovered_equivalences += 1;
                     writeln!(self.proof_output, "= {} {} 0", atom.0, lit.0).unwrap();
                     self.relgraph.add_equivalence(atom, lit);
                     self.equivalence_graph.insert(atom.pos(), lit);
                     self.equivalence_graph.insert(atom.neg(), !lit);
                 }
                 Some(_) => unreachable!(),
+                None if *level < base_level => {
+                    if let Some(failed) = self.failed_assumptions(assumptions) {
+                        return SolveResult::Unsat(failed);
+                    }
+                    self.context.push_frame();
+                    *level = base_level;
+                    for &lit in assumptions {
+                        self.relgraph.select_literal(lit, base_level);
+                    }
+                }
                 None => {
                     let Some(next_selection) = self.relgraph.next_literal() else {
-                        return Some(self.construct_solution());
+                        return SolveResult::Sat(self.construct_solution(assumptions));
                     };
                     let (atom, sign) = next_selection.atom_and_sign();
                     let replaced = selected_literals.insert(atom, sign);
                     assert!(replaced.is_none(), "atom {} already selected", atom.0);
                     literal_at_level.push(next_selection);
+                    self.stats.decisions += 1;
                     self.context.push_frame();
-                    level += 1;
-                    self.relgraph.select_literal(next_selection, level);
+                    *level += 1;
+                    self.relgraph.select_literal(next_selection, *level);
                 }
             }
         }
     }
 
-    fn construct_solution(self) -> Vec<Literal> {
+    // Assumptions contradicted at the root or by each other, which selecting would not report.
+    fn failed_assumptions(&mut self, assumptions: &[Literal]) -> Option<Vec<Literal>> {
+        let root = self.relgraph.all_assignments();
+        let representatives = path_compress(&self.equivalence_graph);
+        let mut selected = HashMap::new();
+        for &lit in assumptions {
+            let rep = representatives.get(&lit).copied().unwrap_or(lit);
+            if root.get(&rep.atom()) == Some(&(!rep).sign()) {
+                return Some(vec![lit]);
+            }
+            if let Some(&other) = selected.get(&!rep) {
+                return Some(vec![other, lit]);
+            }
+            selected.insert(rep, lit);
+        }
+        None
+    }
+
+    fn construct_solution(&mut self, assumptions: &[Literal]) -> Vec<Literal> {
         let mut result = self.relgraph.all_assignments();
         let compressed_equivalence_graph = path_compress(&self.equivalence_graph);
         for (&x, &y) in compressed_equivalence_graph.iter() {
@@ -131,6 +330,9 @@ impl Solver {
                 result.insert(x.atom(), x.sign());
             }
         }
+        for &lit in assumptions {
+            result.entry(lit.atom()).or_insert(lit.sign());
+        }
         for &atom in &self.required_atoms {
             result.entry(atom).or_insert(Sign::Neg);
         }
@@ -1,64 +1,309 @@
 use std::{
     collections::HashSet,
     fs::File,
-    io::{self, BufReader, Write},
+    io::{self, BufWriter, Write},
+    process,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
-use sat::{Literal, parse};
-use satsolver::Solver;
+use sat::{
+    Cnf, Icnf, IcnfStep, Literal, Wcnf, input,
+    parse::{self, ParseOptions},
+};
+use satsolver::{Limits, Options, SolveResult, Solver, Stats, maxsat, unsat_core};
 
 use clap::Parser;
-use std::path::PathBuf;
+use signal_hook::{self as signal, consts::SIGINT};
+use std::path::{Path, PathBuf};
 
 /// SAT Solver program.
 #[derive(Parser)]
 struct Opts {
-    /// Sets the input CNF file to use
+    /// Sets the input CNF file to use, optionally compressed, or `-` for stdin
     #[clap()]
     cnf: PathBuf,
 
     /// Sets the optional EDRAT proof file to write to
     #[clap(short, long)]
     edrat: Option<PathBuf>,
+
+    /// Makes solver runs and proofs reproducible byte-for-byte
+    #[clap(long)]
+    deterministic: bool,
+
+    /// Accepts clauses beyond the declared count and comments after the header
+    #[clap(long)]
+    lenient: bool,
+
+    /// Reads a weighted (W)CNF file and searches for a minimum-cost model
+    #[clap(long, conflicts_with = "edrat")]
+    maxsat: bool,
+
+    /// Reads an iCNF file and answers each `a` query in turn, keeping learnt rules between them
+    #[clap(long, conflicts_with = "maxsat")]
+    incremental: bool,
+
+    /// Gives up with `s UNKNOWN` after this many seconds of search
+    #[clap(long, conflicts_with = "maxsat")]
+    time_limit: Option<f64>,
+
+    /// Gives up with `s UNKNOWN` after this many conflicts
+    #[clap(long, conflicts_with = "maxsat")]
+    conflict_limit: Option<u64>,
+
+    /// Gives up with `s UNKNOWN` after this many decisions
+    #[clap(long, conflicts_with = "maxsat")]
+    decision_limit: Option<u64>,
+
+    /// Prints a `c` progress line about every this many seconds of search
+    #[clap(long, conflicts_with = "maxsat")]
+    progress: Option<f64>,
+
+    /// Writes a minimised unsatisfiable subset of the input clauses to this DIMACS file
+    #[clap(long, conflicts_with_all = ["maxsat", "incremental"])]
+    core: Option<PathBuf>,
 }
 
+impl Opts {
+    fn configure(&self, solver: &mut Solver) {
+        solver.set_limits(Limits {
+            time: self.time_limit.map(Duration::from_secs_f64),
+            conflicts: self.conflict_limit,
+            decisions: self.decision_limit,
+        });
+        if let Some(interval) = self.progress {
+            solver.set_progress(Duration::from_secs_f64(interval), print_progress);
+        }
+        handle_interrupts(solver.terminate_flag());
+    }
+
+    fn proof_output(&self) -> Box<dyn Write> {
+        match &self.edrat {
+            Some(path) => Box::new(File::create(path).unwrap()),
+            None => Box::new(io::sink()),
+        }
+    }
+}
+
+const MODEL_LINE_WIDTH: usize = 78;
+
 fn main() {
+    let start = Instant::now();
     let opts = Opts::parse();
-    let rules = Vec::from_iter(parse::rules(BufReader::new(File::open(opts.cnf).unwrap())));
-    let proof_output: Box<dyn Write> = match opts.edrat {
-        Some(path) => Box::new(File::create(path).unwrap()),
-        None => Box::new(io::sink()),
+    let input = match input::open(&opts.cnf) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("{}: {e}", opts.cnf.display());
+            process::exit(1);
+        }
     };
-    let Some(solver) = Solver::new(rules.clone(), proof_output) else {
-        println!("v UNSATISFIABLE");
-        return;
+    let parse_options = ParseOptions {
+        lenient: opts.lenient,
+    };
+    let options = Options {
+        deterministic: opts.deterministic,
     };
-    match solver.solve() {
-        Some(solution) => {
-            let solution_lits: HashSet<Literal> = solution.iter().copied().collect();
-            println!("v SATISFIABLE");
-            for lit in solution {
-                print!("{} ", lit.0);
+    if opts.maxsat {
+        let wcnf = match parse::try_wcnf_with(input, parse_options) {
+            Ok(wcnf) => wcnf,
+            Err(e) => {
+                eprintln!("{}: {e}", opts.cnf.display());
+                process::exit(1);
             }
-            println!("0");
-            for &x in &solution_lits {
-                assert!(
-                    !solution_lits.contains(&!x),
-                    "Solution contains both {} and {}",
-                    x.0,
-                    (!x).0
-                );
+        };
+        run_maxsat(&wcnf, &options);
+        return;
+    }
+    if opts.incremental {
+        let icnf = match parse::try_icnf_with(input, parse_options) {
+            Ok(icnf) => icnf,
+            Err(e) => {
+                eprintln!("{}: {e}", opts.cnf.display());
+                process::exit(1);
+            }
+        };
+        let mut solver = Solver::with_options([], opts.proof_output(), options).unwrap();
+        opts.configure(&mut solver);
+        run_incremental(icnf, solver);
+        return;
+    }
+    let cnf = match parse::try_cnf_with(input, parse_options) {
+        Ok(cnf) => cnf,
+        Err(e) => {
+            eprintln!("{}: {e}", opts.cnf.display());
+            process::exit(1);
+        }
+    };
+    println!(
+        "c parsed {} variables and {} clauses in {:.3}s",
+        cnf.num_vars,
+        cnf.num_clauses(),
+        start.elapsed().as_secs_f64()
+    );
+    let solve_start = Instant::now();
+    let (result, stats) =
+        match Solver::with_options(cnf.clauses.clone(), opts.proof_output(), options.clone()) {
+            Some(mut solver) => {
+                opts.configure(&mut solver);
+                (solver.solve_with_assumptions(&[]), *solver.stats())
             }
-            for rule in rules {
-                assert!(
-                    rule.iter().any(|&x| solution_lits.contains(&x)),
-                    "No literal in rule {:?}",
-                    rule.iter().map(|&x| x.0).collect::<Vec<_>>()
-                );
+            None => (SolveResult::Unsat(Vec::new()), Stats::default()),
+        };
+    println!("c search took {:.3}s", solve_start.elapsed().as_secs_f64());
+    print_stats(&stats);
+    let solution = match result {
+        SolveResult::Sat(solution) => solution,
+        SolveResult::Unsat(_) => {
+            if let Some(path) = &opts.core {
+                write_core(&cnf, &options, path);
             }
+            println!("s UNSATISFIABLE");
+            process::exit(20);
+        }
+        SolveResult::Unknown => {
+            println!("s UNKNOWN");
+            process::exit(0);
         }
-        None => {
-            println!("v UNSATISFIABLE");
+    };
+    let solution_lits: HashSet<Literal> = solution.iter().copied().collect();
+    for &x in &solution_lits {
+        assert!(
+            !solution_lits.contains(&!x),
+            "Solution contains both {} and {}",
+            x.0,
+            (!x).0
+        );
+    }
+    for rule in &cnf.clauses {
+        assert!(
+            rule.iter().any(|&x| solution_lits.contains(&x)),
+            "No literal in rule {:?}",
+            rule.iter().map(|&x| x.0).collect::<Vec<_>>()
+        );
+    }
+    println!("s SATISFIABLE");
+    print_model(&solution);
+    process::exit(10);
+}
+
+// Prints `v` lines no wider than `MODEL_LINE_WIDTH`, ending with the terminating `0`.
+fn print_model(model: &[Literal]) {
+    let mut line = String::from("v");
+    for token in model
+        .iter()
+        .map(|lit| lit.0.to_string())
+        .chain(["0".into()])
+    {
+        if line.len() + 1 + token.len() > MODEL_LINE_WIDTH {
+            println!("{line}");
+            line = String::from("v");
         }
+        line.push(' ');
+        line.push_str(&token);
     }
+    println!("{line}");
+}
+
+fn run_maxsat(wcnf: &Wcnf, options: &Options) {
+    let solution = maxsat::solve(wcnf, options, |cost| println!("o {cost}"));
+    let Some(solution) = solution else {
+        println!("s UNSATISFIABLE");
+        process::exit(20);
+    };
+    assert_eq!(wcnf.cost(&solution.model), solution.cost);
+    let model = HashSet::<Literal>::from_iter(solution.model.iter().copied());
+    for rule in &wcnf.hard {
+        assert!(
+            rule.iter().any(|x| model.contains(x)),
+            "No literal in hard rule {:?}",
+            rule.iter().map(|&x| x.0).collect::<Vec<_>>()
+        );
+    }
+    println!("s OPTIMUM FOUND");
+    print_model(&solution.model);
+    process::exit(30);
+}
+
+fn run_incremental(icnf: Icnf, mut solver: Solver) {
+    let terminate = solver.terminate_flag();
+    let mut rules = Vec::new();
+    for step in icnf.steps {
+        let assumptions = match step {
+            IcnfStep::Clause(clause) => {
+                rules.push(clause);
+                continue;
+            }
+            IcnfStep::Query(assumptions) => assumptions,
+        };
+        solver.add_rules(rules.drain(..));
+        match solver.solve_with_assumptions(&assumptions) {
+            SolveResult::Sat(model) => {
+                println!("s SATISFIABLE");
+                print_model(&model);
+            }
+            SolveResult::Unsat(_) => println!("s UNSATISFIABLE"),
+            SolveResult::Unknown => {
+                println!("s UNKNOWN");
+                if terminate.load(Ordering::Relaxed) {
+                    break;
+                }
+            }
+        }
+    }
+    print_stats(solver.stats());
+}
+
+fn write_core(cnf: &Cnf, options: &Options, path: &Path) {
+    let core = unsat_core::extract(&cnf.clauses, options, true).unwrap();
+    let mut core_cnf = Cnf::from_clauses(core.iter().map(|&i| cnf.clauses[i].clone()));
+    core_cnf.num_vars = cnf.num_vars;
+    core_cnf.add_comment(format!(
+        "unsatisfiable core of {} out of {} clauses",
+        core.len(),
+        cnf.num_clauses()
+    ));
+    let written = File::create(path).and_then(|file| core_cnf.write_dimacs(BufWriter::new(file)));
+    if let Err(e) = written {
+        eprintln!("{}: {e}", path.display());
+        process::exit(1);
+    }
+    println!("c wrote {} core clauses to {}", core.len(), path.display());
+}
+
+fn print_stats(stats: &Stats) {
+    let Stats {
+        decisions,
+        conflicts,
+        learnt_rules,
+        backjumps,
+        invalidated_selections,
+        discovered_singletons,
+        discovered_binaries,
+        discovered_equivalences,
+    } = stats;
+    println!("c decisions               {decisions}");
+    println!("c conflicts               {conflicts}");
+    println!("c learnt rules            {learnt_rules}");
+    println!("c backjumps               {backjumps}");
+    println!("c invalidated selections  {invalidated_selections}");
+    println!("c discovered singletons   {discovered_singletons}");
+    println!("c discovered binaries     {discovered_binaries}");
+    println!("c discovered equivalences {discovered_equivalences}");
+}
+
+fn print_progress(stats: &Stats) {
+    println!(
+        "c progress: {} decisions, {} conflicts, {} learnt rules",
+        stats.decisions, stats.conflicts, stats.learnt_rules
+    );
+}
+
+// The first SIGINT stops the search so its statistics can still be printed; a second one exits.
+fn handle_interrupts(terminate: Arc<AtomicBool>) {
+    signal::flag::register_conditional_shutdown(SIGINT, 130, terminate.clone()).unwrap();
+    signal::flag::register(SIGINT, terminate).unwrap();
 }
@@ -0,0 +1,108 @@
+use std::{collections::BTreeMap, io};
+
+use sat::{Atom, Literal, Wcnf};
+
+use crate::{Options, SolveResult, Solver};
+
+pub struct MaxSatSolution {
+    pub cost: u64,
+    pub model: Vec<Literal>,
+}
+
+// Linear SAT-UNSAT search: every soft clause gets a relaxation atom, and each round re-solves
+// the hard clauses with the weight of the true relaxation atoms bounded below the best cost
+// found so far. `on_improvement` sees each improved cost. Returns `None` if the hard clauses
+// are unsatisfiable.
+pub fn solve(
+    wcnf: &Wcnf,
+    options: &Options,
+    mut on_improvement: impl FnMut(u64),
+) -> Option<MaxSatSolution> {
+    let relaxation = Vec::from_iter((0..wcnf.soft.len()).map(|i| Atom(wcnf.num_vars + 1 + i)));
+    let mut rules = wcnf.hard.clone();
+    for ((_, clause), &r) in wcnf.soft.iter().zip(&relaxation) {
+        rules.push(Vec::from_iter(clause.iter().copied().chain([r.pos()])));
+    }
+    let mut best: Option<MaxSatSolution> = None;
+    loop {
+        let mut round_rules = rules.clone();
+        if let Some(best) = &best {
+            if best.cost == 0 {
+                break;
+            }
+            let weighted = Vec::from_iter(
+                wcnf.soft
+                    .iter()
+                    .zip(&relaxation)
+                    .map(|(&(weight, _), &r)| (weight, r)),
+            );
+            let next_atom = wcnf.num_vars + relaxation.len() + 1;
+            round_rules.extend(bound_below(&weighted, best.cost, next_atom));
+        }
+        let Some(SolveResult::Sat(model)) =
+            Solver::with_options(round_rules, Box::new(io::sink()), options.clone())
+                .map(Solver::solve)
+        else {
+            break;
+        };
+        let model = restrict(&model, wcnf.num_vars);
+        let cost = wcnf.cost(&model);
+        assert!(best.as_ref().is_none_or(|best| cost < best.cost));
+        on_improvement(cost);
+        best = Some(MaxSatSolution { cost, model });
+    }
+    best
+}
+
+// The model over atoms `1..=num_vars`, with atoms the solver left out set false.
+fn restrict(model: &[Literal], num_vars: usize) -> Vec<Literal> {
+    let mut result = Vec::from_iter((1..=num_vars).map(|atom| Atom(atom).neg()));
+    for &lit in model {
+        if lit.atom().0 <= num_vars {
+            result[lit.atom().0 - 1] = lit;
+        }
+    }
+    result
+}
+
+// Generalized totalizer encoding of `sum(weight for true atoms) < bound`, using fresh atoms from
+// `next_atom` on. Each node has an output atom per reachable partial sum (sums of at least
+// `bound` share one), implied by its children's outputs; the root's `bound` output is forbidden.
+fn bound_below(weighted: &[(u64, Atom)], bound: u64, mut next_atom: usize) -> Vec<Vec<Literal>> {
+    let mut rules = Vec::new();
+    let mut fresh = || {
+        next_atom += 1;
+        Atom(next_atom - 1)
+    };
+    let mut layer = Vec::from_iter(
+        weighted
+            .iter()
+            .map(|&(weight, atom)| BTreeMap::from([(weight.min(bound), atom)])),
+    );
+    while layer.len() > 1 {
+        let mut merged = Vec::new();
+        let mut nodes = layer.into_iter();
+        while let Some(left) = nodes.next() {
+            let Some(right) = nodes.next() else {
+                merged.push(left);
+                break;
+            };
+            let mut outputs = BTreeMap::new();
+            let mut output = |sum: u64| *outputs.entry(sum.min(bound)).or_insert_with(&mut fresh);
+            for (&a, &x) in left.iter().chain(&right) {
+                rules.push(vec![x.neg(), output(a).pos()]);
+            }
+            for (&a, &x) in &left {
+                for (&b, &y) in &right {
+                    rules.push(vec![x.neg(), y.neg(), output(a.saturating_add(b)).pos()]);
+                }
+            }
+            merged.push(outputs);
+        }
+        layer = merged;
+    }
+    if let Some(&root) = layer.first().and_then(|root| root.get(&bound)) {
+        rules.push(vec![root.neg()]);
+    }
+    rules
+}
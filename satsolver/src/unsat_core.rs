@@ -0,0 +1,57 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+};
+
+use sat::{Atom, Literal};
+
+use crate::{Options, SolveResult, Solver};
+
+// Each rule gets a selector atom it is weakened by, so assuming a subset of the selectors solves
+// that subset of the rules and the failed assumptions name the rules the refutation used. With
+// `minimise`, rules are then dropped one at a time while the rest stay unsatisfiable, leaving a
+// core from which no single rule can be removed. Returns indices into `rules`, or `None` if the
+// rules are satisfiable.
+pub fn extract(rules: &[Vec<Literal>], options: &Options, minimise: bool) -> Option<Vec<usize>> {
+    let num_vars = rules
+        .iter()
+        .flatten()
+        .map(|lit| lit.atom().0)
+        .max()
+        .unwrap_or(0);
+    let selectors = Vec::from_iter((0..rules.len()).map(|i| Atom(num_vars + 1 + i).pos()));
+    let index = HashMap::<Literal, usize>::from_iter(selectors.iter().copied().zip(0..));
+    let selected_rules = rules
+        .iter()
+        .zip(&selectors)
+        .map(|(rule, &selector)| Vec::from_iter(rule.iter().copied().chain([!selector])));
+    let mut solver = Solver::with_options(selected_rules, Box::new(io::sink()), options.clone())?;
+    let mut solve = |indices: &[usize]| {
+        let assumptions = Vec::from_iter(indices.iter().map(|&i| selectors[i]));
+        match solver.solve_with_assumptions(&assumptions) {
+            SolveResult::Sat(_) => None,
+            SolveResult::Unsat(failed) => {
+                Some(HashSet::<usize>::from_iter(failed.iter().map(|x| index[x])))
+            }
+            SolveResult::Unknown => unreachable!("no limits were set"),
+        }
+    };
+
+    let mut candidates = Vec::from_iter(solve(&Vec::from_iter(0..rules.len()))?);
+    candidates.sort();
+    if !minimise {
+        return Some(candidates);
+    }
+    // A rule needed by a set of rules is needed by every unsatisfiable subset of it, so the
+    // smaller core a successful removal reports never drops one already found necessary.
+    let mut necessary = Vec::new();
+    while let Some(candidate) = candidates.pop() {
+        let rest = Vec::from_iter(necessary.iter().chain(&candidates).copied());
+        match solve(&rest) {
+            Some(failed) => candidates.retain(|i| failed.contains(i)),
+            None => necessary.push(candidate),
+        }
+    }
+    necessary.sort();
+    Some(necessary)
+}
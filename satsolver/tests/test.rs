@@ -0,0 +1,250 @@
+use std::{cell::Cell, rc::Rc, sync::atomic::Ordering, time::Duration};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use sat::{Atom, Icnf, IcnfStep, Literal, Wcnf};
+use satsolver::{Limits, Options, SolveResult, Solver, Stats, maxsat, unsat_core};
+
+fn random_clause(rng: &mut StdRng, num_vars: usize) -> Vec<Literal> {
+    let len = rng.random_range(1..4);
+    Vec::from_iter((0..len).map(|_| {
+        let atom = Atom(rng.random_range(1..=num_vars));
+        if rng.random_bool(0.5) {
+            atom.pos()
+        } else {
+            atom.neg()
+        }
+    }))
+}
+
+fn all_models(num_vars: usize) -> impl Iterator<Item = Vec<Literal>> {
+    (0..1u32 << num_vars).map(move |bits| {
+        Vec::from_iter((0..num_vars).map(|i| {
+            let atom = Atom(i + 1);
+            if bits >> i & 1 == 1 {
+                atom.pos()
+            } else {
+                atom.neg()
+            }
+        }))
+    })
+}
+
+fn satisfiable(rules: &[Vec<Literal>], assumptions: &[Literal], num_vars: usize) -> bool {
+    all_models(num_vars).any(|model| {
+        assumptions.iter().all(|lit| model.contains(lit))
+            && rules
+                .iter()
+                .all(|rule| rule.iter().any(|lit| model.contains(lit)))
+    })
+}
+
+fn brute_force(wcnf: &Wcnf) -> Option<u64> {
+    all_models(wcnf.num_vars)
+        .filter(|model| {
+            wcnf.hard
+                .iter()
+                .all(|clause| clause.iter().any(|lit| model.contains(lit)))
+        })
+        .map(|model| wcnf.cost(&model))
+        .min()
+}
+
+#[test]
+fn test_maxsat_matches_brute_force() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(161803);
+
+    for _ in 0..40 {
+        let num_vars = rng.random_range(1..7);
+        let mut wcnf = Wcnf::new();
+        for _ in 0..rng.random_range(0..6) {
+            wcnf.add_hard(random_clause(&mut rng, num_vars));
+        }
+        for _ in 0..rng.random_range(0..8) {
+            let weight = rng.random_range(1..6);
+            wcnf.add_soft(weight, random_clause(&mut rng, num_vars));
+        }
+        wcnf.num_vars = num_vars;
+
+        let mut costs = Vec::new();
+        let solution = maxsat::solve(&wcnf, &Options::default(), |cost| costs.push(cost));
+        let expected = brute_force(&wcnf);
+        assert_eq!(solution.as_ref().map(|s| s.cost), expected);
+        assert!(costs.is_sorted_by(|a, b| a > b));
+        if let Some(solution) = solution {
+            assert_eq!(costs.last(), Some(&solution.cost));
+            assert_eq!(solution.model.len(), num_vars);
+            assert_eq!(wcnf.cost(&solution.model), solution.cost);
+        }
+    }
+}
+
+#[test]
+fn test_assumptions_match_brute_force() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(271828);
+
+    for _ in 0..40 {
+        let num_vars = rng.random_range(1..9);
+        let rules = Vec::from_iter(
+            (0..rng.random_range(0..4 * num_vars)).map(|_| random_clause(&mut rng, num_vars)),
+        );
+        let Some(mut solver) = Solver::new(rules.clone(), Box::new(std::io::sink())) else {
+            continue;
+        };
+        for _ in 0..10 {
+            let assumptions = Vec::from_iter(
+                (0..rng.random_range(0..4)).map(|_| random_clause(&mut rng, num_vars)[0]),
+            );
+            match solver.solve_with_assumptions(&assumptions) {
+                SolveResult::Sat(model) => {
+                    assert!(assumptions.iter().all(|lit| model.contains(lit)));
+                    for rule in &rules {
+                        assert!(rule.iter().any(|lit| model.contains(lit)), "{rule:?}");
+                    }
+                }
+                SolveResult::Unsat(failed) => {
+                    assert!(failed.iter().all(|lit| assumptions.contains(lit)));
+                    assert!(!satisfiable(&rules, &failed, num_vars));
+                }
+                SolveResult::Unknown => unreachable!(),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_incremental_matches_brute_force() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(141421);
+
+    for _ in 0..20 {
+        let num_vars = rng.random_range(1..9);
+        let mut icnf = Icnf::new();
+        for _ in 0..rng.random_range(0..40) {
+            if rng.random_bool(0.8) {
+                icnf.add_clause(random_clause(&mut rng, num_vars));
+            } else {
+                let len = rng.random_range(0..3);
+                icnf.add_query(Vec::from_iter(
+                    (0..len).map(|_| random_clause(&mut rng, num_vars)[0]),
+                ));
+            }
+        }
+
+        let mut solver = Solver::new([], Box::new(std::io::sink())).unwrap();
+        let mut rules = Vec::new();
+        for step in icnf.steps {
+            match step {
+                IcnfStep::Clause(clause) => {
+                    solver.add_rules([clause.clone()]);
+                    rules.push(clause);
+                }
+                IcnfStep::Query(assumptions) => {
+                    let result = solver.solve_with_assumptions(&assumptions);
+                    assert_eq!(
+                        matches!(result, SolveResult::Sat(_)),
+                        satisfiable(&rules, &assumptions, num_vars)
+                    );
+                }
+            }
+        }
+    }
+}
+
+// Four pigeons in three holes.
+fn pigeonhole() -> Vec<Vec<Literal>> {
+    let var = |pigeon: usize, hole: usize| Atom(pigeon * 3 + hole + 1);
+    let mut rules = Vec::from_iter((0..4).map(|p| Vec::from_iter((0..3).map(|h| var(p, h).pos()))));
+    for h in 0..3 {
+        for p in 0..4 {
+            for q in p + 1..4 {
+                rules.push(vec![var(p, h).neg(), var(q, h).neg()]);
+            }
+        }
+    }
+    rules
+}
+
+#[test]
+fn test_limits_and_termination() {
+    let mut solver = Solver::new(pigeonhole(), Box::new(std::io::sink())).unwrap();
+    for limits in [
+        Limits {
+            time: Some(Duration::ZERO),
+            ..Limits::default()
+        },
+        Limits {
+            conflicts: Some(0),
+            ..Limits::default()
+        },
+        Limits {
+            decisions: Some(0),
+            ..Limits::default()
+        },
+    ] {
+        solver.set_limits(limits);
+        assert_eq!(solver.solve_with_assumptions(&[]), SolveResult::Unknown);
+    }
+
+    solver.set_limits(Limits::default());
+    let terminate = solver.terminate_flag();
+    terminate.store(true, Ordering::Relaxed);
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveResult::Unknown);
+    terminate.store(false, Ordering::Relaxed);
+    assert_eq!(solver.solve(), SolveResult::Unsat(Vec::new()));
+}
+
+#[test]
+fn test_stats() {
+    let mut solver = Solver::new(pigeonhole(), Box::new(std::io::sink())).unwrap();
+    let reports = Rc::new(Cell::new(0));
+    solver.set_progress(Duration::ZERO, {
+        let reports = reports.clone();
+        move |_: &Stats| reports.set(reports.get() + 1)
+    });
+    solver.set_limits(Limits {
+        decisions: Some(2),
+        ..Limits::default()
+    });
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveResult::Unknown);
+    let stats = *solver.stats();
+    assert_eq!(stats.decisions, 2);
+    assert!(reports.get() > 0);
+
+    solver.set_limits(Limits::default());
+    assert_eq!(
+        solver.solve_with_assumptions(&[]),
+        SolveResult::Unsat(Vec::new())
+    );
+    let stats = solver.stats();
+    assert!(stats.decisions > 2);
+    assert!(stats.conflicts > 0);
+    assert!(stats.learnt_rules <= stats.conflicts);
+    assert!(stats.backjumps <= stats.conflicts);
+}
+
+#[test]
+fn test_unsat_core_matches_brute_force() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(173205);
+
+    for _ in 0..40 {
+        let num_vars = rng.random_range(1..7);
+        let rules = Vec::from_iter(
+            (0..rng.random_range(0..6 * num_vars)).map(|_| random_clause(&mut rng, num_vars)),
+        );
+        let core = unsat_core::extract(&rules, &Options::default(), false);
+        let minimal = unsat_core::extract(&rules, &Options::default(), true);
+        assert_eq!(core.is_none(), satisfiable(&rules, &[], num_vars));
+        assert_eq!(core.is_none(), minimal.is_none());
+        let (Some(core), Some(minimal)) = (core, minimal) else {
+            continue;
+        };
+        let subset = |indices: &[usize]| Vec::from_iter(indices.iter().map(|&i| rules[i].clone()));
+        assert!(!satisfiable(&subset(&core), &[], num_vars));
+        assert!(!satisfiable(&subset(&minimal), &[], num_vars));
+        for i in 0..minimal.len() {
+            let mut smaller = minimal.clone();
+            smaller.remove(i);
+            assert!(satisfiable(&subset(&smaller), &[], num_vars));
+        }
+    }
+}
@@ -2,12 +2,13 @@ use std::ops::Not;
 
 use either::Either::{Left, Right};
 use loopy_relations::CreationContext;
+use relation_pipeline::FxBuildHasher;
 use sat::{Atom, Level, Literal, LiteralCause, RuleIndex, Sign};
 
 use crate::{RelGraph, signal};
 
 impl RelGraph {
-    pub fn construct(context: &mut CreationContext) -> Self {
+    pub fn construct(context: &mut CreationContext<FxBuildHasher>) -> Self {
         let (rules_input, base_rules) = context.new_frameless_input::<(RuleIndex, Literal)>();
         let base_rules = base_rules.save();
 
@@ -1,17 +1,20 @@
 use std::collections::HashMap;
 
-use loopy_relations::{FirstOccurrencesInput, FramelessInput, Input, InterruptId, Output};
+use loopy_relations::{FirstOccurrencesInput, FramelessInput, Input, InterruptId};
+use relation_pipeline::{FxBuildHasher, ops::Dynamic};
 use sat::{Atom, Level, Literal, LiteralCause, RuleIndex, Sign};
 
 mod construct;
 
 pub mod signal;
 
+type Output<T> = loopy_relations::Output<T, Dynamic<'static, T>, FxBuildHasher>;
+
 pub struct RelGraph {
-    rules_input: FramelessInput<(RuleIndex, Literal)>,
-    assigned_input: FirstOccurrencesInput<Literal, LiteralCause>,
-    level_input: Input<Level>,
-    equivalence_input: FramelessInput<(Atom, Literal)>,
+    rules_input: FramelessInput<(RuleIndex, Literal), FxBuildHasher>,
+    assigned_input: FirstOccurrencesInput<Literal, LiteralCause, FxBuildHasher>,
+    level_input: Input<Level, FxBuildHasher>,
+    equivalence_input: FramelessInput<(Atom, Literal), FxBuildHasher>,
     next_literal: Output<Literal>,
     resolution_output: Output<Literal>,
     resolution_level_output: Output<Level>,
@@ -36,9 +39,16 @@ impl RelGraph {
     }
 
     pub fn add_rule(&self, rule_index: RuleIndex, new_rule: &[Literal]) {
-        for &x in new_rule {
-            self.rules_input.insert((rule_index, x));
-        }
+        self.rules_input
+            .extend(new_rule.iter().map(|&x| (rule_index, x)));
+    }
+
+    pub fn add_rules<'a>(&self, rules: impl IntoIterator<Item = (RuleIndex, &'a [Literal])>) {
+        self.rules_input.extend(
+            rules
+                .into_iter()
+                .flat_map(|(rule_index, rule)| rule.iter().map(move |&x| (rule_index, x))),
+        );
     }
 
     pub fn next_literal(&mut self) -> Option<Literal> {
@@ -51,7 +61,7 @@ impl RelGraph {
         self.level_input.insert(level);
     }
 
-    pub fn all_assignments(mut self) -> HashMap<Atom, Sign> {
+    pub fn all_assignments(&mut self) -> HashMap<Atom, Sign> {
         HashMap::from_iter(self.assigned_output.iter().map(Literal::atom_and_sign))
     }
 
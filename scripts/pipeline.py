@@ -2,10 +2,12 @@
 import subprocess
 import sys
 from pathlib import Path
-from typing import List
+from typing import Collection, List
 
 
-def execute_subprocess(command: List[str], verbose: bool) -> str:
+def execute_subprocess(
+    command: List[str], verbose: bool, returncodes: Collection[int] = (0,)
+) -> str:
     stdout_lines: List[str] = []
     stderr_option = (
         None if verbose else subprocess.PIPE
@@ -25,7 +27,7 @@ def execute_subprocess(command: List[str], verbose: bool) -> str:
 
         process.wait()
 
-        if process.returncode != 0:
+        if process.returncode not in returncodes:
             raise subprocess.CalledProcessError(
                 process.returncode, command, "".join(stdout_lines)
             )
@@ -47,13 +49,15 @@ def run_solver(
             "--package",
             "satsolver",
             str(cnf_path),
+            "--lenient",
             "-e",
             str(edrat_proof_path),
         ],
         verbose,
+        returncodes=(10, 20),
     )
 
-    is_sat = "v SATISFIABLE" in stdout_content
+    is_sat = "s SATISFIABLE" in stdout_content.splitlines()
 
     if not is_sat:
         _ = execute_subprocess(
@@ -0,0 +1,124 @@
+pub trait Codec: Sized {
+    fn encode(&self, out: &mut Vec<u8>);
+    fn decode(input: &mut &[u8]) -> Self;
+}
+
+fn take<'a>(input: &mut &'a [u8], n: usize) -> &'a [u8] {
+    let (head, tail) = input.split_at(n);
+    *input = tail;
+    head
+}
+
+macro_rules! impl_codec_int {
+    ($($t:ty),*) => {
+        $(
+            impl Codec for $t {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+                fn decode(input: &mut &[u8]) -> Self {
+                    Self::from_le_bytes(take(input, size_of::<Self>()).try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Codec for usize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out);
+    }
+    fn decode(input: &mut &[u8]) -> Self {
+        u64::decode(input).try_into().unwrap()
+    }
+}
+
+impl Codec for isize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as i64).encode(out);
+    }
+    fn decode(input: &mut &[u8]) -> Self {
+        i64::decode(input).try_into().unwrap()
+    }
+}
+
+impl Codec for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+    fn decode(input: &mut &[u8]) -> Self {
+        u8::decode(input) != 0
+    }
+}
+
+impl Codec for char {
+    fn encode(&self, out: &mut Vec<u8>) {
+        u32::from(*self).encode(out);
+    }
+    fn decode(input: &mut &[u8]) -> Self {
+        char::from_u32(u32::decode(input)).unwrap()
+    }
+}
+
+impl Codec for () {
+    fn encode(&self, _out: &mut Vec<u8>) {}
+    fn decode(_input: &mut &[u8]) -> Self {}
+}
+
+impl<T: Codec> Codec for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.is_some().encode(out);
+        if let Some(x) = self {
+            x.encode(out);
+        }
+    }
+    fn decode(input: &mut &[u8]) -> Self {
+        bool::decode(input).then(|| T::decode(input))
+    }
+}
+
+impl<T: Codec> Codec for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for x in self {
+            x.encode(out);
+        }
+    }
+    fn decode(input: &mut &[u8]) -> Self {
+        let len = usize::decode(input);
+        (0..len).map(|_| T::decode(input)).collect()
+    }
+}
+
+impl Codec for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+    fn decode(input: &mut &[u8]) -> Self {
+        let len = usize::decode(input);
+        String::from_utf8(take(input, len).to_vec()).unwrap()
+    }
+}
+
+macro_rules! impl_codec_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: Codec),*> Codec for ($($name,)*) {
+            #[allow(non_snake_case)]
+            fn encode(&self, out: &mut Vec<u8>) {
+                let ($($name,)*) = self;
+                $($name.encode(out);)*
+            }
+            fn decode(input: &mut &[u8]) -> Self {
+                ($($name::decode(input),)*)
+            }
+        }
+    };
+}
+
+impl_codec_tuple!(A);
+impl_codec_tuple!(A, B);
+impl_codec_tuple!(A, B, C);
+impl_codec_tuple!(A, B, C, D);
@@ -0,0 +1,269 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    hash::{BuildHasher, Hash, RandomState},
+    io::{Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use l2_map::L2Map;
+
+pub use self::codec::Codec;
+
+mod codec;
+
+const MIN_COMPACTION_BYTES: u64 = 1 << 16;
+
+static NEXT_LOG_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Debug)]
+pub struct SpillConfig {
+    pub dir: PathBuf,
+    pub max_resident: usize,
+}
+
+impl SpillConfig {
+    pub fn new(dir: impl Into<PathBuf>, max_resident: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            max_resident,
+        }
+    }
+}
+
+// Keys stay in memory; only the (K2, V) groups of cold keys are paged out to an
+// append-only log, which is compacted once it is mostly dead space.
+pub struct SpillMap<K1: Clone + Eq + Hash, K2: Clone + Eq + Hash, V, S: BuildHasher = RandomState> {
+    config: SpillConfig,
+    hot: L2Map<K1, K2, V, 2, S>,
+    last_used: HashMap<K1, u64, S>,
+    clock: u64,
+    cold: HashMap<K1, Extent, S>,
+    cold_len: usize,
+    log: Option<Log>,
+}
+
+#[derive(Clone, Copy)]
+struct Extent {
+    offset: u64,
+    bytes: u64,
+    len: usize,
+}
+
+struct Log {
+    path: PathBuf,
+    file: File,
+    end: u64,
+    dead: u64,
+}
+
+impl<K1, K2, V, S> SpillMap<K1, K2, V, S>
+where
+    K1: Clone + Eq + Hash,
+    K2: Clone + Eq + Hash + Codec,
+    V: Codec,
+    S: BuildHasher + Default,
+{
+    pub fn new(config: SpillConfig) -> Self {
+        Self {
+            config,
+            hot: L2Map::new(),
+            last_used: HashMap::default(),
+            clock: 0,
+            cold: HashMap::default(),
+            cold_len: 0,
+            log: None,
+        }
+    }
+}
+
+impl<K1, K2, V, S> SpillMap<K1, K2, V, S>
+where
+    K1: Clone + Eq + Hash,
+    K2: Clone + Eq + Hash + Codec,
+    V: Codec,
+    S: BuildHasher,
+{
+    pub fn len(&self) -> usize {
+        self.hot.len() + self.cold_len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn resident_len(&self) -> usize {
+        self.hot.len()
+    }
+
+    pub fn spilled_len(&self) -> usize {
+        self.cold_len
+    }
+
+    pub fn insert(&mut self, k1: K1, k2: K2, v: V) -> Option<V> {
+        self.fault_in(&k1);
+        let result = self.hot.insert(k1.clone(), k2, v);
+        self.touch(&k1);
+        self.evict_except(&k1);
+        result
+    }
+
+    pub fn get_mut(&mut self, k1: &K1, k2: &K2) -> Option<&mut V> {
+        self.fault_in(k1);
+        self.touch(k1);
+        self.evict_except(k1);
+        self.hot.get_mut(k1, k2)
+    }
+
+    pub fn remove(&mut self, k1: &K1, k2: &K2) -> Option<V> {
+        self.fault_in(k1);
+        let result = self.hot.remove(k1, k2);
+        self.touch(k1);
+        self.evict_except(k1);
+        result
+    }
+
+    pub fn get_iter<'a>(&'a mut self, k1: &'a K1) -> impl Iterator<Item = &'a (K2, V)> {
+        self.fault_in(k1);
+        self.touch(k1);
+        self.evict_except(k1);
+        self.hot.get_iter(k1)
+    }
+
+    fn touch(&mut self, k1: &K1) {
+        if self.hot.get_iter(k1).next().is_none() {
+            self.last_used.remove(k1);
+            return;
+        }
+        self.clock += 1;
+        self.last_used.insert(k1.clone(), self.clock);
+    }
+
+    fn fault_in(&mut self, k1: &K1) {
+        let Some(extent) = self.cold.remove(k1) else {
+            return;
+        };
+        let log = self.log.as_mut().unwrap();
+        let mut buf = log.read(extent);
+        let mut input = buf.as_slice();
+        for _ in 0..extent.len {
+            let k2 = K2::decode(&mut input);
+            let v = V::decode(&mut input);
+            self.hot.insert(k1.clone(), k2, v);
+        }
+        assert!(input.is_empty(), "spilled group has trailing bytes");
+        self.cold_len -= extent.len;
+        log.dead += extent.bytes;
+        if log.dead >= MIN_COMPACTION_BYTES && log.dead * 2 >= log.end {
+            buf.clear();
+            log.compact(&mut self.cold, &mut buf);
+        }
+    }
+
+    fn evict_except(&mut self, keep: &K1) {
+        if self.hot.len() <= self.config.max_resident {
+            return;
+        }
+        let mut candidates = Vec::from_iter(
+            self.last_used
+                .iter()
+                .filter(|&(k1, _)| k1 != keep)
+                .map(|(k1, &t)| (t, k1.clone())),
+        );
+        candidates.sort_unstable_by_key(|&(t, _)| t);
+        let target = self.config.max_resident / 2;
+        let mut buf = Vec::new();
+        for (_, k1) in candidates {
+            if self.hot.len() <= target {
+                break;
+            }
+            self.spill(k1, &mut buf);
+        }
+    }
+
+    fn spill(&mut self, k1: K1, buf: &mut Vec<u8>) {
+        buf.clear();
+        let mut len = 0;
+        for (k2, v) in self.hot.remove_all(&k1) {
+            k2.encode(buf);
+            v.encode(buf);
+            len += 1;
+        }
+        self.last_used.remove(&k1);
+        let log = match &mut self.log {
+            Some(log) => log,
+            None => self.log.insert(Log::create(&self.config)),
+        };
+        let extent = log.append(buf, len);
+        self.cold_len += len;
+        self.cold.insert(k1, extent);
+    }
+}
+
+impl Log {
+    fn create(config: &SpillConfig) -> Self {
+        let id = NEXT_LOG_ID.fetch_add(1, Ordering::Relaxed);
+        let path = config
+            .dir
+            .join(format!("spill-{}-{}.log", process::id(), id));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .unwrap_or_else(|e| panic!("failed to create spill file {}: {e}", path.display()));
+        Self {
+            path,
+            file,
+            end: 0,
+            dead: 0,
+        }
+    }
+
+    fn append(&mut self, buf: &[u8], len: usize) -> Extent {
+        let offset = self.end;
+        self.file.seek(SeekFrom::Start(offset)).unwrap();
+        self.file.write_all(buf).unwrap();
+        self.end += buf.len() as u64;
+        Extent {
+            offset,
+            bytes: buf.len() as u64,
+            len,
+        }
+    }
+
+    fn read(&mut self, extent: Extent) -> Vec<u8> {
+        let mut buf = vec![0; extent.bytes as usize];
+        self.file.seek(SeekFrom::Start(extent.offset)).unwrap();
+        self.file.read_exact(&mut buf).unwrap();
+        buf
+    }
+
+    fn compact<K, S: BuildHasher>(&mut self, cold: &mut HashMap<K, Extent, S>, buf: &mut Vec<u8>) {
+        let mut extents = Vec::from_iter(cold.values_mut());
+        extents.sort_unstable_by_key(|extent| extent.offset);
+        // Live extents only ever move towards the start, so copying in offset order never
+        // overwrites data that has yet to be copied.
+        let mut end = 0;
+        for extent in extents {
+            buf.resize(extent.bytes as usize, 0);
+            self.file.seek(SeekFrom::Start(extent.offset)).unwrap();
+            self.file.read_exact(buf).unwrap();
+            self.file.seek(SeekFrom::Start(end)).unwrap();
+            self.file.write_all(buf).unwrap();
+            extent.offset = end;
+            end += extent.bytes;
+        }
+        self.file.set_len(end).unwrap();
+        self.end = end;
+        self.dead = 0;
+    }
+}
+
+impl Drop for Log {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use spill_map::{Codec, SpillConfig, SpillMap};
+
+#[test]
+fn test_spill_equivalence() {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(31416);
+
+    let config = SpillConfig::new(std::env::temp_dir(), 40);
+    let mut spill_map = SpillMap::<i32, (u8, String), i64>::new(config);
+    let mut hash_map: HashMap<i32, BTreeMap<(u8, String), i64>> = HashMap::new();
+    let mut max_spilled = 0;
+
+    for _ in 0..20000 {
+        let k1: i32 = rng.random_range(0..50);
+        let k2 = (rng.random_range(0..8), "x".repeat(rng.random_range(0..4)));
+        match rng.random_range(0..4) {
+            0 => {
+                let removed = spill_map.remove(&k1, &k2);
+                let expected = hash_map.get_mut(&k1).and_then(|set| set.remove(&k2));
+                assert_eq!(removed, expected);
+            }
+            1 => {
+                let v: i64 = rng.random_range(-100..100);
+                let inserted = spill_map.insert(k1, k2.clone(), v);
+                let expected = hash_map.entry(k1).or_default().insert(k2, v);
+                assert_eq!(inserted, expected);
+            }
+            2 => {
+                if let Some(v) = spill_map.get_mut(&k1, &k2) {
+                    *v += 1;
+                }
+                if let Some(v) = hash_map.get_mut(&k1).and_then(|set| set.get_mut(&k2)) {
+                    *v += 1;
+                }
+            }
+            _ => {
+                let actual = BTreeMap::from_iter(spill_map.get_iter(&k1).cloned());
+                let expected = hash_map.get(&k1).cloned().unwrap_or_default();
+                assert_eq!(actual, expected);
+            }
+        }
+        assert!(spill_map.resident_len() <= 40 + 8);
+        assert_eq!(
+            spill_map.len(),
+            hash_map.values().map(BTreeMap::len).sum::<usize>()
+        );
+        max_spilled = max_spilled.max(spill_map.spilled_len());
+    }
+    assert!(max_spilled > 0);
+
+    for k1 in 0..50 {
+        let actual = BTreeMap::from_iter(spill_map.get_iter(&k1).cloned());
+        assert_eq!(actual, hash_map.remove(&k1).unwrap_or_default());
+    }
+}
+
+fn roundtrip<T: Codec + PartialEq + Debug>(value: T) {
+    let mut buf = Vec::new();
+    value.encode(&mut buf);
+    let mut input = buf.as_slice();
+    assert_eq!(T::decode(&mut input), value);
+    assert!(input.is_empty());
+}
+
+#[test]
+fn test_codec_roundtrip() {
+    roundtrip((
+        vec![Some(-3i32), None, Some(7)],
+        String::from("spill"),
+        (usize::MAX, true, 'λ'),
+        (),
+    ));
+    roundtrip(i128::MIN);
+    roundtrip(Vec::<String>::new());
+}
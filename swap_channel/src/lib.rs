@@ -1,38 +1,76 @@
-use std::{cell::RefCell, collections::VecDeque, mem, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    mem,
+    rc::Rc,
+};
 
 use derive_where::derive_where;
 
 #[derive_where(Clone)]
-pub struct Sender<T>(Rc<RefCell<VecDeque<T>>>);
+pub struct Sender<T>(Rc<Shared<T>>);
 
 pub struct Receiver<T> {
     // Should always be empty
     receive_queue: VecDeque<T>,
-    send_queue: Rc<RefCell<VecDeque<T>>>,
+    shared: Rc<Shared<T>>,
+}
+
+struct Shared<T> {
+    send_queue: RefCell<VecDeque<T>>,
+    connected: Cell<bool>,
 }
 
 impl<T> Sender<T> {
     pub fn send(&self, t: T) {
-        self.0.borrow_mut().push_back(t);
+        if self.is_connected() {
+            self.0.send_queue.borrow_mut().push_back(t);
+        }
+    }
+    pub fn send_all(&self, ts: impl IntoIterator<Item = T>) {
+        if self.is_connected() {
+            self.0.send_queue.borrow_mut().extend(ts);
+        }
+    }
+    // Once the receiver is dropped, further sends are discarded.
+    pub fn is_connected(&self) -> bool {
+        self.0.connected.get()
+    }
+    // Number of values sent but not yet received.
+    pub fn backlog(&self) -> usize {
+        self.0.send_queue.borrow().len()
     }
 }
 
 impl<T> Receiver<T> {
     pub fn try_recv(&mut self) -> Option<T> {
-        self.send_queue.borrow_mut().pop_front()
+        self.shared.send_queue.borrow_mut().pop_front()
     }
     pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
-        mem::swap(&mut self.receive_queue, &mut self.send_queue.borrow_mut());
+        mem::swap(
+            &mut self.receive_queue,
+            &mut self.shared.send_queue.borrow_mut(),
+        );
         self.receive_queue.drain(..)
     }
 }
 
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.connected.set(false);
+        self.shared.send_queue.take();
+    }
+}
+
 pub fn new<T>() -> (Sender<T>, Receiver<T>) {
-    let send_queue = Rc::new(RefCell::new(VecDeque::new()));
-    let sender = Sender(send_queue.clone());
+    let shared = Rc::new(Shared {
+        send_queue: RefCell::new(VecDeque::new()),
+        connected: Cell::new(true),
+    });
+    let sender = Sender(shared.clone());
     let receiver = Receiver {
         receive_queue: VecDeque::new(),
-        send_queue,
+        shared,
     };
     (sender, receiver)
 }